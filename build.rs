@@ -18,6 +18,23 @@ fn main() {
         &manifest_dir.join("assets"),
         &executable_path.join("assets"),
     );
+
+    emit_gl_configuration();
+}
+
+/// Mirrors the `gl_es` and `gl_extension` configuration of the `gl` crate, so code can follow its generated API
+fn emit_gl_configuration() {
+    println!("cargo:rustc-check-cfg=cfg(gl_es)");
+    println!("cargo:rustc-check-cfg=cfg(gl_extension, values(any()))");
+
+    if env::var("DEP_GL_BINDINGS_API").is_ok_and(|api| api == "gles") {
+        println!("cargo:rustc-cfg=gl_es");
+    }
+    for extension in env::var("DEP_GL_BINDINGS_EXTENSIONS").unwrap_or_default().split(',') {
+        if !extension.is_empty() {
+            println!("cargo:rustc-cfg=gl_extension=\"{extension}\"");
+        }
+    }
 }
 
 fn locate_target_dir_from_output_dir<'a>(mut target_dir_search: &'a Path, profile: &'a str) -> Option<&'a Path> {
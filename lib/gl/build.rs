@@ -2,29 +2,179 @@ extern crate gl_generator;
 
 use std::env;
 use std::fs::File;
+use std::io::{self, Write};
 use std::path::Path;
 
-use gl_generator::{Api, Fallbacks, GlobalGenerator, Profile, Registry};
+use gl_generator::{Api, Fallbacks, GlobalGenerator, Profile, Registry, StructGenerator};
 
-const BINDNGS_OUTPUT_FILE: &'static str = "bindings.rs";
+const BINDNGS_OUTPUT_FILE: &str = "bindings.rs";
+const STRUCT_BINDINGS_OUTPUT_FILE: &str = "struct_bindings.rs";
+const DISPATCH_BINDINGS_OUTPUT_FILE: &str = "dispatch_bindings.rs";
+const SYNC_TYPE: &str = "types::GLsync";
+
+/// Extensions which can be enabled through a cargo feature of the same name, along with the APIs supporting them.
+/// Features are ignored for other APIs, as the generator rejects unsupported extensions.
+const EXTENSION_FEATURES: [(&str, &str, &[Api]); 3] = [
+    ("CARGO_FEATURE_EXT_TEXTURE_FILTER_ANISOTROPIC", "GL_EXT_texture_filter_anisotropic", &[Api::Gl, Api::Gles2]),
+    ("CARGO_FEATURE_KHR_DEBUG", "GL_KHR_debug", &[Api::Gl, Api::Gles2]),
+    // Timer queries are core on desktop
+    ("CARGO_FEATURE_EXT_DISJOINT_TIMER_QUERY", "GL_EXT_disjoint_timer_query", &[Api::Gles2]),
+];
 
 type ApiVersion = (u8, u8);
 
 fn main() {
     let out_dir = env::var("OUT_DIR")
         .expect("Couldn't find build directory from 'OUT_DIR' environment variable!");
-    let mut file_gl = File::create(&Path::new(&out_dir).join(BINDNGS_OUTPUT_FILE))
+    let mut file_gl = File::create(Path::new(&out_dir).join(BINDNGS_OUTPUT_FILE))
         .expect("Failed to create gl bindings file!");
+    let mut file_gl_struct = File::create(Path::new(&out_dir).join(STRUCT_BINDINGS_OUTPUT_FILE))
+        .expect("Failed to create gl struct bindings file!");
+    let mut file_gl_dispatch = File::create(Path::new(&out_dir).join(DISPATCH_BINDINGS_OUTPUT_FILE))
+        .expect("Failed to create gl dispatch bindings file!");
+
+    for variable in &["GL_API", "GL_VERSION", "GL_PROFILE", "GL_EXTENSIONS"] {
+        println!("cargo:rerun-if-env-changed={}", variable);
+    }
+
     let (api, version) = get_api_and_version();
-    Registry::new(api, version, Profile::Core, Fallbacks::All, [])
-        .write_bindings(GlobalGenerator, &mut file_gl)
+    let profile = get_profile();
+    let extensions = get_extensions(api);
+    let registry = Registry::new(api, version, profile, Fallbacks::All, extensions.iter().map(String::as_str).collect::<Vec<_>>());
+    registry.write_bindings(GlobalGenerator, &mut file_gl)
         .expect("Failed to write gl bindings!");
+    // Function pointers owned by a struct, for contexts which can't share the global ones
+    registry.write_bindings(StructGenerator, &mut file_gl_struct)
+        .expect("Failed to write gl struct bindings!");
+    write_dispatch_bindings(&registry, &mut file_gl_dispatch)
+        .expect("Failed to write gl dispatch bindings!");
+
+    emit_configuration(api, &extensions);
 }
 
+/// Writes a function for every command, which calls through the struct bindings of the `Context` current on the
+/// calling thread or the global bindings if there is none. The safe wrappers are built on these, so they work with both.
+fn write_dispatch_bindings(registry: &Registry, dest: &mut File) -> io::Result<()> {
+    // The generated parameter types refer to this module
+    writeln!(dest, "mod __gl_imports {{ pub use std::os::raw; }}")?;
+    for cmd in &registry.cmds {
+        let return_suffix = if cmd.proto.ty == "()" { String::new() } else { format!(" -> {}", cmd.proto.ty) };
+        let arguments = cmd.params.iter()
+            .map(|param| param.ident.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        // Both bindings declare their own opaque sync type, so sync objects are cast between them
+        let struct_arguments = cmd.params.iter()
+            .map(|param| if param.ty == SYNC_TYPE { format!("{}.cast()", param.ident) } else { param.ident.clone() })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let struct_return_cast = if cmd.proto.ty == SYNC_TYPE { ".cast()" } else { "" };
+        let params = cmd.params.iter()
+            .map(|param| format!("{}: {}", param.ident, param.ty))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            dest,
+            "#[inline] #[allow(non_snake_case, clippy::missing_safety_doc, clippy::too_many_arguments)] \
+            pub unsafe fn {name}({params}){return_suffix} {{ \
+            ::context::dispatch(|gl| gl.{name}({struct_arguments}){struct_return_cast}, || global::{name}({arguments})) }}",
+            name = cmd.proto.ident,
+            params = params,
+            return_suffix = return_suffix,
+            arguments = arguments,
+            struct_arguments = struct_arguments,
+            struct_return_cast = struct_return_cast)?;
+    }
+
+    Ok(())
+}
+
+/// The API defaults to GLES 3.0 for emscripten or the `gles3` feature, and GL 4.5 otherwise.
+/// It may be overridden through the `GL_API` (`gl` / `gles`) and `GL_VERSION` (`major.minor`) environment variables.
 fn get_api_and_version() -> (Api, ApiVersion) {
-    if cfg!(target_os = "emscripten") {
-        (Api::Gles2, (3, 0))
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let default_api = if target_os == "emscripten" || env::var_os("CARGO_FEATURE_GLES3").is_some() {
+        Api::Gles2
     } else {
-        (Api::Gl, (4, 5))
+        Api::Gl
+    };
+
+    let api = match env::var("GL_API") {
+        Ok(api) => match api.to_lowercase().as_str() {
+            "gl" => Api::Gl,
+            "gles" | "gles2" | "gles3" => Api::Gles2,
+            _ => panic!("Unsupported GL_API '{}', expected 'gl' or 'gles'", api),
+        },
+        Err(_) => default_api,
+    };
+
+    let version = match env::var("GL_VERSION") {
+        Ok(version) => parse_version(&version)
+            .unwrap_or_else(|| panic!("Invalid GL_VERSION '{}', expected 'major.minor'", version)),
+        Err(_) => if api == Api::Gles2 { (3, 0) } else { (4, 5) },
+    };
+
+    // The safe wrappers rely on these versions being available
+    let minimum_version = if api == Api::Gles2 { (3, 0) } else { (4, 5) };
+    if version < minimum_version {
+        panic!("GL_VERSION {}.{} is below the supported minimum of {}.{} for {}",
+               version.0, version.1, minimum_version.0, minimum_version.1, api);
     }
+
+    (api, version)
+}
+
+fn parse_version(version: &str) -> Option<ApiVersion> {
+    let mut parts = version.trim().splitn(2, '.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Selected through the `GL_PROFILE` (`core` / `compatibility`) environment variable, defaults to core
+fn get_profile() -> Profile {
+    match env::var("GL_PROFILE") {
+        Ok(profile) => match profile.to_lowercase().as_str() {
+            "core" => Profile::Core,
+            "compat" | "compatibility" => Profile::Compatibility,
+            _ => panic!("Unsupported GL_PROFILE '{}', expected 'core' or 'compatibility'", profile),
+        },
+        Err(_) => Profile::Core,
+    }
+}
+
+/// Extensions enabled through cargo features, plus the comma-separated `GL_EXTENSIONS` environment variable
+fn get_extensions(api: Api) -> Vec<String> {
+    let mut extensions: Vec<String> = EXTENSION_FEATURES.iter()
+        .filter(|&&(feature, _, apis)| env::var_os(feature).is_some() && apis.contains(&api))
+        .map(|&(_, extension, _)| String::from(extension))
+        .collect();
+
+    if let Ok(additional_extensions) = env::var("GL_EXTENSIONS") {
+        extensions.extend(additional_extensions.split(',')
+            .map(str::trim)
+            .filter(|extension| !extension.is_empty())
+            .map(String::from));
+    }
+
+    extensions.sort();
+    extensions.dedup();
+    extensions
+}
+
+/// Exposes the selected API to this crate through `cfg(gl_es)` and `cfg(gl_extension = "...")`,
+/// and to dependents through the `DEP_GL_BINDINGS_API` and `DEP_GL_BINDINGS_EXTENSIONS` variables
+fn emit_configuration(api: Api, extensions: &[String]) {
+    println!("cargo:rustc-check-cfg=cfg(gl_es)");
+    println!("cargo:rustc-check-cfg=cfg(gl_extension, values(any()))");
+
+    if api == Api::Gles2 {
+        println!("cargo:rustc-cfg=gl_es");
+    }
+    for extension in extensions {
+        println!("cargo:rustc-cfg=gl_extension=\"{}\"", extension);
+    }
+
+    println!("cargo:api={}", if api == Api::Gles2 { "gles" } else { "gl" });
+    println!("cargo:extensions={}", extensions.join(","));
 }
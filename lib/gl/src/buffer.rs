@@ -1,21 +1,86 @@
+use std::mem::MaybeUninit;
+use std::ops::{BitOr, Deref, DerefMut};
+
 use ::{gl, gl::RawHandle};
+use bytemuck::Pod;
+#[cfg(not(gl_es))]
+use context_info;
 use gl_raw_handle_derive::RawHandle;
+use state_cache;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
 pub struct BufferTarget(gl::GLenum);
 
 pub const ARRAY_BUFFER: BufferTarget = BufferTarget(gl::ARRAY_BUFFER);
 pub const ELEMENT_ARRAY_BUFFER: BufferTarget = BufferTarget(gl::ELEMENT_ARRAY_BUFFER);
+pub const COPY_READ_BUFFER: BufferTarget = BufferTarget(gl::COPY_READ_BUFFER);
+pub const COPY_WRITE_BUFFER: BufferTarget = BufferTarget(gl::COPY_WRITE_BUFFER);
+pub const PIXEL_PACK_BUFFER: BufferTarget = BufferTarget(gl::PIXEL_PACK_BUFFER);
+pub const PIXEL_UNPACK_BUFFER: BufferTarget = BufferTarget(gl::PIXEL_UNPACK_BUFFER);
+pub const UNIFORM_BUFFER: BufferTarget = BufferTarget(gl::UNIFORM_BUFFER);
+pub const TRANSFORM_FEEDBACK_BUFFER: BufferTarget = BufferTarget(gl::TRANSFORM_FEEDBACK_BUFFER);
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
 pub struct BufferUsage(gl::GLenum);
 
 pub const STREAM_DRAW: BufferUsage = BufferUsage(gl::STREAM_DRAW);
 pub const STATIC_DRAW: BufferUsage = BufferUsage(gl::STATIC_DRAW);
+pub const DYNAMIC_DRAW: BufferUsage = BufferUsage(gl::DYNAMIC_DRAW);
 pub const STREAM_READ: BufferUsage = BufferUsage(gl::STREAM_READ);
 pub const STATIC_READ: BufferUsage = BufferUsage(gl::STATIC_READ);
+pub const DYNAMIC_READ: BufferUsage = BufferUsage(gl::DYNAMIC_READ);
 pub const STREAM_COPY: BufferUsage = BufferUsage(gl::STREAM_COPY);
 pub const STATIC_COPY: BufferUsage = BufferUsage(gl::STATIC_COPY);
+pub const DYNAMIC_COPY: BufferUsage = BufferUsage(gl::DYNAMIC_COPY);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct MapAccess(gl::GLbitfield);
+
+pub const MAP_READ: MapAccess = MapAccess(gl::MAP_READ_BIT);
+pub const MAP_WRITE: MapAccess = MapAccess(gl::MAP_WRITE_BIT);
+pub const MAP_INVALIDATE_RANGE: MapAccess = MapAccess(gl::MAP_INVALIDATE_RANGE_BIT);
+pub const MAP_INVALIDATE_BUFFER: MapAccess = MapAccess(gl::MAP_INVALIDATE_BUFFER_BIT);
+pub const MAP_FLUSH_EXPLICIT: MapAccess = MapAccess(gl::MAP_FLUSH_EXPLICIT_BIT);
+pub const MAP_UNSYNCHRONIZED: MapAccess = MapAccess(gl::MAP_UNSYNCHRONIZED_BIT);
+#[cfg(not(gl_es))]
+pub const MAP_PERSISTENT: MapAccess = MapAccess(gl::MAP_PERSISTENT_BIT);
+#[cfg(not(gl_es))]
+pub const MAP_COHERENT: MapAccess = MapAccess(gl::MAP_COHERENT_BIT);
+
+impl MapAccess {
+    #[must_use]
+    pub fn contains(self, access: Self) -> bool {
+        self.0 & access.0 == access.0
+    }
+}
+
+impl BitOr for MapAccess {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self::Output { Self(unsafe { self.raw_handle() | rhs.raw_handle() }) }
+}
+
+#[cfg(not(gl_es))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct StorageFlags(gl::GLbitfield);
+
+#[cfg(not(gl_es))]
+pub const STORAGE_DYNAMIC: StorageFlags = StorageFlags(gl::DYNAMIC_STORAGE_BIT);
+#[cfg(not(gl_es))]
+pub const STORAGE_CLIENT: StorageFlags = StorageFlags(gl::CLIENT_STORAGE_BIT);
+#[cfg(not(gl_es))]
+pub const STORAGE_MAP_READ: StorageFlags = StorageFlags(gl::MAP_READ_BIT);
+#[cfg(not(gl_es))]
+pub const STORAGE_MAP_WRITE: StorageFlags = StorageFlags(gl::MAP_WRITE_BIT);
+#[cfg(not(gl_es))]
+pub const STORAGE_MAP_PERSISTENT: StorageFlags = StorageFlags(gl::MAP_PERSISTENT_BIT);
+#[cfg(not(gl_es))]
+pub const STORAGE_MAP_COHERENT: StorageFlags = StorageFlags(gl::MAP_COHERENT_BIT);
+
+#[cfg(not(gl_es))]
+impl BitOr for StorageFlags {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self::Output { Self(unsafe { self.raw_handle() | rhs.raw_handle() }) }
+}
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
 pub struct BufferId(gl::GLuint);
@@ -45,7 +110,9 @@ pub fn gen_buffer() -> BufferId {
 }
 
 pub fn bind_buffer(target: BufferTarget, buffer_id: BufferId) {
-    unsafe { gl::BindBuffer(target.raw_handle(), buffer_id.raw_handle()) };
+    if state_cache::bind_buffer_required(target.0, buffer_id.0) {
+        unsafe { gl::BindBuffer(target.raw_handle(), buffer_id.raw_handle()) };
+    }
 }
 
 pub fn buffer_data<T>(target: BufferTarget, data: &[T], usage: BufferUsage) {
@@ -55,6 +122,204 @@ pub fn buffer_data<T>(target: BufferTarget, data: &[T], usage: BufferUsage) {
     }
 }
 
+pub fn buffer_data_uninitialized(target: BufferTarget, size: usize, usage: BufferUsage) {
+    unsafe { gl::BufferData(target.raw_handle(), size as gl::GLsizeiptr, std::ptr::null(), usage.raw_handle()) };
+}
+
+/// Allocates immutable storage of `size` bytes for the buffer bound to `target`
+#[cfg(not(gl_es))]
+pub fn buffer_storage_uninitialized(target: BufferTarget, size: usize, flags: StorageFlags) {
+    unsafe { gl::BufferStorage(target.raw_handle(), size as gl::GLsizeiptr, std::ptr::null(), flags.raw_handle()) };
+}
+
+/// Whether [`buffer_storage_uninitialized`] and persistent mapping are available, which requires desktop GL 4.4
+/// or `GL_ARB_buffer_storage`
+#[cfg(not(gl_es))]
+#[must_use]
+pub fn buffer_storage_supported() -> bool {
+    context_info::version_at_least(4, 4) || context_info::has_extension("GL_ARB_buffer_storage")
+}
+
+/// `offset` is given in bytes from the start of the buffer
+pub fn buffer_sub_data<T>(target: BufferTarget, offset: usize, data: &[T]) {
+    let size: gl::GLsizeiptr = std::mem::size_of_val(data) as isize;
+    unsafe {
+        gl::BufferSubData(target.raw_handle(), offset as gl::GLintptr, size, data.as_ptr().cast());
+    }
+}
+
+/// `offset` is given in bytes from the start of the buffer
+#[cfg(not(gl_es))]
+pub fn get_buffer_sub_data<T: Pod>(target: BufferTarget, offset: usize, data: &mut [T]) {
+    let size: gl::GLsizeiptr = std::mem::size_of_val(data) as isize;
+    unsafe {
+        gl::GetBufferSubData(target.raw_handle(), offset as gl::GLintptr, size, data.as_mut_ptr().cast());
+    }
+}
+
+/// All values are given in bytes
+pub fn copy_buffer_sub_data(
+    read_target: BufferTarget,
+    write_target: BufferTarget,
+    read_offset: usize,
+    write_offset: usize,
+    size: usize,
+) {
+    unsafe {
+        gl::CopyBufferSubData(
+            read_target.raw_handle(),
+            write_target.raw_handle(),
+            read_offset as gl::GLintptr,
+            write_offset as gl::GLintptr,
+            size as gl::GLsizeiptr,
+        )
+    };
+}
+
+/// Mapped range of a buffer, which is unmapped again once dropped
+#[derive(Debug)]
+pub struct MappedBuffer<T> {
+    target: BufferTarget,
+    buffer_id: BufferId,
+    data: *mut T,
+    len: usize,
+    writable: bool,
+}
+
+impl<T> MappedBuffer<T> {
+    /// Unmaps the buffer, returning `false` if its contents became corrupt while being mapped
+    pub fn unmap(self) -> bool {
+        let (target, buffer_id) = (self.target, self.buffer_id);
+        std::mem::forget(self);
+        unmap_bound_buffer(target, buffer_id)
+    }
+}
+
+impl<T> Deref for MappedBuffer<T> {
+    type Target = [T];
+    fn deref(&self) -> &Self::Target { unsafe { std::slice::from_raw_parts(self.data, self.len) } }
+}
+
+impl<T> DerefMut for MappedBuffer<T> {
+    /// # Panics
+    /// - The range was mapped without [`MAP_WRITE`]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        assert!(self.writable, "Buffer range was mapped read only");
+        unsafe { std::slice::from_raw_parts_mut(self.data, self.len) }
+    }
+}
+
+impl<T> Drop for MappedBuffer<T> {
+    fn drop(&mut self) {
+        unmap_bound_buffer(self.target, self.buffer_id);
+    }
+}
+
+/// Binds `buffer_id` to `target` and maps `len` elements of type `T` starting at byte `offset` of it.
+/// `access` must include [`MAP_READ`], use [`map_buffer_range_write`] for write only mappings.
+/// Returns `None` if the driver refused the mapping or returned a pointer unsuitably aligned for `T`
+///
+/// # Safety
+/// The buffer must neither be deleted nor have its storage respecified while the mapping is alive
+///
+/// # Panics
+/// - `access` doesn't include [`MAP_READ`]
+#[must_use]
+pub unsafe fn map_buffer_range<T: Pod>(
+    target: BufferTarget,
+    buffer_id: BufferId,
+    offset: usize,
+    len: usize,
+    access: MapAccess,
+) -> Option<MappedBuffer<T>> {
+    assert!(access.contains(MAP_READ), "Mapping a buffer range without MAP_READ leaves its contents undefined");
+    map_range(target, buffer_id, offset, len, access)
+}
+
+/// Binds `buffer_id` to `target` and maps `len` elements of type `T` starting at byte `offset` of it for writing.
+/// The contents of a range mapped without [`MAP_READ`] are undefined, so they're only exposed as `MaybeUninit`.
+/// Returns `None` if the driver refused the mapping or returned a pointer unsuitably aligned for `T`
+///
+/// # Safety
+/// The buffer must neither be deleted nor have its storage respecified while the mapping is alive
+///
+/// # Panics
+/// - `access` doesn't include [`MAP_WRITE`]
+#[must_use]
+pub unsafe fn map_buffer_range_write<T: Pod>(
+    target: BufferTarget,
+    buffer_id: BufferId,
+    offset: usize,
+    len: usize,
+    access: MapAccess,
+) -> Option<MappedBuffer<MaybeUninit<T>>> {
+    assert!(access.contains(MAP_WRITE), "Mapping a buffer range for writing requires MAP_WRITE");
+    map_range(target, buffer_id, offset, len, access)
+}
+
+unsafe fn map_range<T>(
+    target: BufferTarget,
+    buffer_id: BufferId,
+    offset: usize,
+    len: usize,
+    access: MapAccess,
+) -> Option<MappedBuffer<T>> {
+    bind_buffer(target, buffer_id);
+    let size = len * std::mem::size_of::<T>();
+    let data = gl::MapBufferRange(target.raw_handle(), offset as gl::GLintptr, size as gl::GLsizeiptr, access.raw_handle())
+        .cast::<T>();
+
+    if data.is_null() {
+        None
+    } else if data.align_offset(std::mem::align_of::<T>()) != 0 {
+        unmap_buffer(target);
+        None
+    } else {
+        Some(MappedBuffer { target, buffer_id, data, len, writable: access.contains(MAP_WRITE) })
+    }
+}
+
+/// Unmaps `buffer_id` even if another buffer was bound to `target` since mapping it, keeping that binding
+fn unmap_bound_buffer(target: BufferTarget, buffer_id: BufferId) -> bool {
+    let mut bound_buffer: gl::GLint = 0;
+    unsafe { gl::GetIntegerv(binding_name(target), &mut bound_buffer) };
+    let bound_buffer = bound_buffer as gl::GLuint;
+    if bound_buffer == buffer_id.0 {
+        return unmap_buffer(target);
+    }
+
+    // Bypasses the state cache, which stays valid as the binding is restored right away
+    unsafe {
+        gl::BindBuffer(target.raw_handle(), buffer_id.raw_handle());
+        let unmapped = gl::UnmapBuffer(target.raw_handle()) == gl::TRUE;
+        gl::BindBuffer(target.raw_handle(), bound_buffer);
+        unmapped
+    }
+}
+
+fn binding_name(target: BufferTarget) -> gl::GLenum {
+    match target.0 {
+        gl::ARRAY_BUFFER => gl::ARRAY_BUFFER_BINDING,
+        gl::ELEMENT_ARRAY_BUFFER => gl::ELEMENT_ARRAY_BUFFER_BINDING,
+        gl::COPY_READ_BUFFER => gl::COPY_READ_BUFFER_BINDING,
+        gl::COPY_WRITE_BUFFER => gl::COPY_WRITE_BUFFER_BINDING,
+        gl::PIXEL_PACK_BUFFER => gl::PIXEL_PACK_BUFFER_BINDING,
+        gl::PIXEL_UNPACK_BUFFER => gl::PIXEL_UNPACK_BUFFER_BINDING,
+        gl::UNIFORM_BUFFER => gl::UNIFORM_BUFFER_BINDING,
+        gl::TRANSFORM_FEEDBACK_BUFFER => gl::TRANSFORM_FEEDBACK_BUFFER_BINDING,
+        _ => unreachable!("Buffer targets are only constructed within this module"),
+    }
+}
+
+/// Both values are given in bytes, `offset` relative to the start of the mapped range
+pub fn flush_mapped_buffer_range(target: BufferTarget, offset: usize, size: usize) {
+    unsafe { gl::FlushMappedBufferRange(target.raw_handle(), offset as gl::GLintptr, size as gl::GLsizeiptr) };
+}
+
+pub fn unmap_buffer(target: BufferTarget) -> bool {
+    unsafe { gl::UnmapBuffer(target.raw_handle()) == gl::TRUE }
+}
+
 pub fn delete_buffers(buffer_ids: &mut [BufferId]) {
     let raw_buffer_ids = buffer_ids.into_iter()
         .map(|buffer_id| unsafe { buffer_id.raw_handle() })
@@ -63,10 +328,14 @@ pub fn delete_buffers(buffer_ids: &mut [BufferId]) {
         gl::DeleteBuffers(buffer_ids.len() as gl::GLsizei, raw_buffer_ids.as_ptr());
     }
     buffer_ids.into_iter()
-        .for_each(|buffer_id| buffer_id.0 = 0);
+        .for_each(|buffer_id| {
+            state_cache::forget_buffer(buffer_id.0);
+            buffer_id.0 = 0;
+        });
 }
 
 pub fn delete_buffer(buffer_id: &mut BufferId) {
     unsafe { gl::DeleteBuffers(1, &buffer_id.raw_handle()) };
+    state_cache::forget_buffer(buffer_id.0);
     buffer_id.0 = 0;
 }
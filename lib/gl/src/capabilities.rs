@@ -1,5 +1,6 @@
 use ::{gl, gl::RawHandle};
 use gl_raw_handle_derive::RawHandle;
+use state_cache;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
 pub struct Capability(gl::GLenum);
@@ -9,11 +10,19 @@ pub const CULL_FACE: Capability = Capability(gl::CULL_FACE);
 pub const DEPTH_TEST: Capability = Capability(gl::DEPTH_TEST);
 pub const STENCIL_TEST: Capability = Capability(gl::STENCIL_TEST);
 pub const SCISSOR_TEST: Capability = Capability(gl::SCISSOR_TEST);
+pub const POLYGON_OFFSET_FILL: Capability = Capability(gl::POLYGON_OFFSET_FILL);
+pub const SAMPLE_ALPHA_TO_COVERAGE: Capability = Capability(gl::SAMPLE_ALPHA_TO_COVERAGE);
+pub const RASTERIZER_DISCARD: Capability = Capability(gl::RASTERIZER_DISCARD);
+pub const PRIMITIVE_RESTART_FIXED_INDEX: Capability = Capability(gl::PRIMITIVE_RESTART_FIXED_INDEX);
 
 pub fn enable(capability: Capability) {
-    unsafe { gl::Enable(capability.raw_handle()) };
+    if state_cache::set_capability_required(capability.0, true) {
+        unsafe { gl::Enable(capability.raw_handle()) };
+    }
 }
 
 pub fn disable(capability: Capability) {
-    unsafe { gl::Disable(capability.raw_handle()) };
+    if state_cache::set_capability_required(capability.0, false) {
+        unsafe { gl::Disable(capability.raw_handle()) };
+    }
 }
@@ -0,0 +1,187 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::os::raw::c_void;
+use std::ptr;
+use std::rc::Rc;
+
+use pipeline;
+use state_cache;
+use sys::struct_bindings::Gl;
+
+thread_local! {
+    /// Keeps the bindings of the current context alive, only touched when switching contexts
+    static CURRENT_CONTEXT: RefCell<Option<Rc<Gl>>> = const { RefCell::new(None) };
+    /// Bindings owned by `CURRENT_CONTEXT`, read by every GL call without reference counting, null if there are none
+    static CURRENT_BINDINGS: Cell<*const Gl> = const { Cell::new(ptr::null()) };
+}
+
+/// Function pointers of a single GL context, for when the process-wide ones loaded through
+/// [`load_with`](::load_with) don't fit, e.g. with several contexts created by different drivers.
+///
+/// Once [made current](Context::make_current), every safe wrapper and [`sys`](::sys) function called on the
+/// calling thread goes through these function pointers, until another context is made current or
+/// [`Context::clear_current`] reverts to the global ones. [`Context::with_current`] does the same for a single
+/// closure, e.g. to upload resources to a hidden loader context in between rendering to a window.
+/// The platform context the pointers were loaded from has to be current whenever GL functions are called.
+///
+/// The safe wrappers aren't duplicated as methods, as they keep the thread-local [`state_cache`](::state_cache)
+/// and [`PipelineState`](::pipeline::PipelineState) tracking in sync, which methods on an arbitrary context
+/// would bypass. Both are invalidated whenever the current context changes.
+///
+/// Contexts are neither `Send` nor `Sync`, as GL contexts are bound to the thread they're current on.
+pub struct Context {
+    gl: Rc<Gl>,
+}
+
+impl Context {
+    /// Loads every function pointer of the context current on the calling thread through `loader`
+    pub fn load_with<F>(loader: F) -> Self
+        where
+            F: FnMut(&'static str) -> *const c_void,
+    {
+        Self { gl: Rc::new(Gl::load_with(loader)) }
+    }
+
+    /// Routes the GL calls of the calling thread through this context's function pointers.
+    ///
+    /// Call this right after making the platform context current. The [`state_cache`](::state_cache) and the
+    /// tracked [`PipelineState`](::pipeline::PipelineState) describe a single context, so both are invalidated.
+    pub fn make_current(&self) {
+        switch_to(Some(Rc::clone(&self.gl)));
+    }
+
+    /// Whether GL calls of the calling thread go through this context's function pointers
+    #[must_use]
+    pub fn is_current(&self) -> bool {
+        ptr::eq(CURRENT_BINDINGS.with(Cell::get), &*self.gl)
+    }
+
+    /// Routes the GL calls of the calling thread through the global function pointers again
+    pub fn clear_current() {
+        switch_to(None);
+    }
+
+    /// Runs `f` with the GL calls of the calling thread going through this context, then routes them through the
+    /// previously current one again, also if `f` panics.
+    ///
+    /// The platform context has to be switched along with it, this only switches the function pointers and
+    /// invalidates the tracked state like [`Context::make_current`]. Nothing is invalidated if this context
+    /// already is current.
+    pub fn with_current<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        if self.is_current() {
+            return f();
+        }
+
+        /// Restores the previous context once dropped, which also happens while unwinding
+        struct Restore(Option<Option<Rc<Gl>>>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                if let Some(previous) = self.0.take() {
+                    switch_to(previous);
+                }
+            }
+        }
+
+        let _restore = Restore(Some(switch_to(Some(Rc::clone(&self.gl)))));
+        f()
+    }
+
+    /// Raw bindings of this context, regardless of which one is current
+    ///
+    /// # Safety
+    /// The context must be current on the calling thread and the arguments must be valid for the called function
+    #[must_use]
+    pub unsafe fn raw(&self) -> &Gl {
+        &self.gl
+    }
+}
+
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("is_current", &self.is_current())
+            .finish()
+    }
+}
+
+/// Calls `current` with the bindings of the context current on the calling thread, `global` if there is none
+#[inline]
+pub(crate) fn dispatch<R>(current: impl FnOnce(&Gl) -> R, global: impl FnOnce() -> R) -> R {
+    // Owned by `CURRENT_CONTEXT` until another context is made current, which GL functions can't do, as GL
+    // must not be called from within GL callbacks either
+    match unsafe { CURRENT_BINDINGS.with(Cell::get).as_ref() } {
+        Some(gl) => current(gl),
+        None => global(),
+    }
+}
+
+/// Makes `gl` the bindings of the calling thread, invalidates the tracked state and returns the previous bindings
+fn switch_to(gl: Option<Rc<Gl>>) -> Option<Rc<Gl>> {
+    let bindings = gl.as_ref().map_or(ptr::null(), Rc::as_ptr);
+    let previous = CURRENT_CONTEXT.with(|context| std::mem::replace(&mut *context.borrow_mut(), gl));
+    CURRENT_BINDINGS.with(|current| current.set(bindings));
+    state_cache::invalidate();
+    pipeline::invalidate_pipeline_state();
+
+    previous
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::ptr;
+
+    use super::Context;
+
+    /// Context without loaded functions, which these tests never call
+    fn unloaded_context() -> Context {
+        Context::load_with(|_| ptr::null())
+    }
+
+    #[test]
+    fn switches_current_context() {
+        let first = unloaded_context();
+        let second = unloaded_context();
+        assert!(!first.is_current());
+
+        first.make_current();
+        assert!(first.is_current());
+        second.make_current();
+        assert!(!first.is_current() && second.is_current());
+
+        Context::clear_current();
+        assert!(!second.is_current());
+    }
+
+    #[test]
+    fn restores_previous_context_after_closure() {
+        let outer = unloaded_context();
+        let inner = unloaded_context();
+        outer.make_current();
+
+        let nested = inner.with_current(|| {
+            assert!(inner.is_current());
+            inner.with_current(|| inner.is_current())
+        });
+        assert!(nested);
+        assert!(outer.is_current() && !inner.is_current());
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| inner.with_current(|| panic!("Closure panicked"))));
+        assert!(result.is_err());
+        assert!(outer.is_current());
+        Context::clear_current();
+    }
+
+    #[test]
+    fn keeps_bindings_alive_while_current() {
+        let context = unloaded_context();
+        context.make_current();
+        let bindings: *const _ = unsafe { context.raw() };
+        drop(context);
+
+        let current = super::CURRENT_CONTEXT.with(|current| current.borrow().as_ref().map(|gl| &**gl as *const _));
+        assert_eq!(current, Some(bindings));
+        Context::clear_current();
+    }
+}
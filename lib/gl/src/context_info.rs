@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::fmt;
+
+use gl;
+
+/// Not part of the generated bindings unless `GL_EXT_texture_filter_anisotropic` is requested
+const MAX_TEXTURE_MAX_ANISOTROPY_EXT: gl::GLenum = 0x84FF;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl Version {
+    #[must_use]
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parses the leading `major.minor` of a `GL_VERSION` string, skipping any `OpenGL ES` prefix,
+    /// e.g. `4.6.0 NVIDIA 535.54` or `OpenGL ES 3.2 Mesa 23.1.0`
+    #[must_use]
+    pub fn parse(version: &str) -> Option<Self> {
+        let (major, minor) = split_version(version)?;
+        let minor_len = minor.find(|c: char| !c.is_ascii_digit()).unwrap_or(minor.len());
+        Some(Self { major, minor: minor[..minor_len].parse().ok()? })
+    }
+
+    /// Parses the leading `major.minor` of a `GL_SHADING_LANGUAGE_VERSION` string, skipping any `OpenGL ES GLSL ES`
+    /// prefix, e.g. `4.60 NVIDIA` or `OpenGL ES GLSL ES 3.20`.
+    ///
+    /// GLSL pads its single digit minor version with a zero, so `4.50` is parsed as 4.5.
+    #[must_use]
+    pub fn parse_shading_language(version: &str) -> Option<Self> {
+        let (major, minor) = split_version(version)?;
+        let mut digits = minor.chars().take_while(char::is_ascii_digit);
+        let minor = digits.next()?.to_digit(10)?;
+        match (digits.next(), digits.next()) {
+            (None | Some('0'), None) => Some(Self { major, minor }),
+            _ => None,
+        }
+    }
+}
+
+/// Splits the first number of `version` off at the following dot, returning it and the remainder after the dot
+fn split_version(version: &str) -> Option<(u32, &str)> {
+    let numbers = version.trim_start_matches(|c: char| !c.is_ascii_digit());
+    let (major, minor) = numbers.split_once('.')?;
+    Some((major.parse().ok()?, minor))
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ContextLimits {
+    pub max_texture_size: usize,
+    pub max_3d_texture_size: usize,
+    pub max_cube_map_texture_size: usize,
+    pub max_array_texture_layers: usize,
+    pub max_renderbuffer_size: usize,
+    pub max_texture_image_units: usize,
+    pub max_combined_texture_image_units: usize,
+    pub max_vertex_attribs: usize,
+    pub max_uniform_block_size: usize,
+    pub max_uniform_buffer_bindings: usize,
+    pub max_draw_buffers: usize,
+    pub max_color_attachments: usize,
+    pub max_samples: usize,
+    pub max_viewport_dims: (usize, usize),
+}
+
+/// Runtime description of the current context, queried once through [`ContextInfo::query`]
+#[derive(Debug, Clone)]
+pub struct ContextInfo {
+    pub vendor: String,
+    pub renderer: String,
+    pub version_string: String,
+    pub shading_language_version_string: String,
+    pub is_es: bool,
+    pub version: Version,
+    pub shading_language_version: Version,
+    pub extensions: HashSet<String>,
+    pub limits: ContextLimits,
+    /// `None` unless `GL_EXT_texture_filter_anisotropic` or `GL_ARB_texture_filter_anisotropic` is supported
+    pub max_texture_max_anisotropy: Option<f32>,
+}
+
+impl ContextInfo {
+    #[must_use]
+    pub fn query() -> Self {
+        let version_string = get_string(gl::VERSION);
+        let shading_language_version_string = get_string(gl::SHADING_LANGUAGE_VERSION);
+        let extensions = extensions();
+        let max_texture_max_anisotropy = if extensions.contains("GL_EXT_texture_filter_anisotropic")
+            || extensions.contains("GL_ARB_texture_filter_anisotropic") {
+            Some(get_float(MAX_TEXTURE_MAX_ANISOTROPY_EXT))
+        } else {
+            None
+        };
+
+        Self {
+            vendor: get_string(gl::VENDOR),
+            renderer: get_string(gl::RENDERER),
+            is_es: version_string.starts_with("OpenGL ES"),
+            version: Version::parse(&version_string).unwrap_or(Version::new(0, 0)),
+            shading_language_version: Version::parse_shading_language(&shading_language_version_string).unwrap_or(Version::new(0, 0)),
+            version_string,
+            shading_language_version_string,
+            extensions,
+            limits: ContextLimits::query(),
+            max_texture_max_anisotropy,
+        }
+    }
+
+    #[must_use]
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extensions.contains(name)
+    }
+
+    #[must_use]
+    pub fn version_at_least(&self, major: u32, minor: u32) -> bool {
+        self.version >= Version::new(major, minor)
+    }
+}
+
+impl ContextLimits {
+    #[must_use]
+    pub fn query() -> Self {
+        let mut viewport_dims: [gl::GLint; 2] = [0; 2];
+        unsafe { gl::GetIntegerv(gl::MAX_VIEWPORT_DIMS, viewport_dims.as_mut_ptr()) };
+
+        Self {
+            max_texture_size: get_usize(gl::MAX_TEXTURE_SIZE),
+            max_3d_texture_size: get_usize(gl::MAX_3D_TEXTURE_SIZE),
+            max_cube_map_texture_size: get_usize(gl::MAX_CUBE_MAP_TEXTURE_SIZE),
+            max_array_texture_layers: get_usize(gl::MAX_ARRAY_TEXTURE_LAYERS),
+            max_renderbuffer_size: get_usize(gl::MAX_RENDERBUFFER_SIZE),
+            max_texture_image_units: get_usize(gl::MAX_TEXTURE_IMAGE_UNITS),
+            max_combined_texture_image_units: get_usize(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS),
+            max_vertex_attribs: get_usize(gl::MAX_VERTEX_ATTRIBS),
+            max_uniform_block_size: get_usize(gl::MAX_UNIFORM_BLOCK_SIZE),
+            max_uniform_buffer_bindings: get_usize(gl::MAX_UNIFORM_BUFFER_BINDINGS),
+            max_draw_buffers: get_usize(gl::MAX_DRAW_BUFFERS),
+            max_color_attachments: get_usize(gl::MAX_COLOR_ATTACHMENTS),
+            max_samples: get_usize(gl::MAX_SAMPLES),
+            max_viewport_dims: (viewport_dims[0].max(0) as usize, viewport_dims[1].max(0) as usize),
+        }
+    }
+}
+
+/// Checks the version of the current context without querying the remaining context information
+#[must_use]
+pub fn version_at_least(major: u32, minor: u32) -> bool {
+    Version::parse(&get_string(gl::VERSION)).is_some_and(|version| version >= Version::new(major, minor))
+}
+
+/// Checks a single extension without querying the remaining context information
+#[must_use]
+pub fn has_extension(name: &str) -> bool {
+    (0..extension_count())
+        .filter_map(extension)
+        .any(|extension| extension == name)
+}
+
+#[must_use]
+pub fn extensions() -> HashSet<String> {
+    (0..extension_count())
+        .filter_map(extension)
+        .collect()
+}
+
+fn extension_count() -> gl::GLuint {
+    get_integer(gl::NUM_EXTENSIONS).max(0) as gl::GLuint
+}
+
+fn extension(index: gl::GLuint) -> Option<String> {
+    let extension = unsafe { gl::GetStringi(gl::EXTENSIONS, index) };
+    if extension.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(extension.cast()) }.to_string_lossy().into_owned())
+    }
+}
+
+fn get_string(name: gl::GLenum) -> String {
+    let value = unsafe { gl::GetString(name) };
+    if value.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(value.cast()) }.to_string_lossy().into_owned()
+    }
+}
+
+fn get_integer(name: gl::GLenum) -> gl::GLint {
+    let mut value: gl::GLint = 0;
+    unsafe { gl::GetIntegerv(name, &mut value) };
+    value
+}
+
+fn get_usize(name: gl::GLenum) -> usize {
+    get_integer(name).max(0) as usize
+}
+
+fn get_float(name: gl::GLenum) -> f32 {
+    let mut value: gl::GLfloat = 0f32;
+    unsafe { gl::GetFloatv(name, &mut value) };
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Version;
+
+    #[test]
+    fn parses_desktop_versions() {
+        assert_eq!(Version::parse("4.6.0 NVIDIA 535.54.03"), Some(Version::new(4, 6)));
+        assert_eq!(Version::parse("4.5 (Core Profile) Mesa 23.1.4"), Some(Version::new(4, 5)));
+        assert_eq!(Version::parse("3.3.0 - Build 31.0.101.2111"), Some(Version::new(3, 3)));
+    }
+
+    #[test]
+    fn parses_es_versions() {
+        assert_eq!(Version::parse("OpenGL ES 3.2 Mesa 23.1.4"), Some(Version::new(3, 2)));
+        assert_eq!(Version::parse("OpenGL ES 3.0 (WebGL 2.0 (OpenGL ES 3.0 Chromium))"), Some(Version::new(3, 0)));
+    }
+
+    #[test]
+    fn parses_shading_language_versions() {
+        assert_eq!(Version::parse_shading_language("4.60 NVIDIA"), Some(Version::new(4, 6)));
+        assert_eq!(Version::parse_shading_language("4.50 - Build 31.0.101.2111"), Some(Version::new(4, 5)));
+        assert_eq!(Version::parse_shading_language("1.10"), Some(Version::new(1, 1)));
+        assert_eq!(Version::parse_shading_language("OpenGL ES GLSL ES 3.20"), Some(Version::new(3, 2)));
+        assert_eq!(
+            Version::parse_shading_language("OpenGL ES GLSL ES 3.00 (WebGL GLSL ES 3.00 (OpenGL ES GLSL ES 3.0 Chromium))"),
+            Some(Version::new(3, 0)));
+    }
+
+    #[test]
+    fn rejects_malformed_versions() {
+        assert_eq!(Version::parse(""), None);
+        assert_eq!(Version::parse("OpenGL ES"), None);
+        assert_eq!(Version::parse("4"), None);
+        assert_eq!(Version::parse("4.x"), None);
+        assert_eq!(Version::parse_shading_language("4.65"), None);
+        assert_eq!(Version::parse_shading_language("4.600"), None);
+        assert_eq!(Version::parse_shading_language("4."), None);
+    }
+}
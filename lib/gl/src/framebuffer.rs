@@ -0,0 +1,269 @@
+use ::{gl, gl::RawHandle};
+use bytemuck::Pod;
+use gl_raw_handle_derive::RawHandle;
+use texture::TextureId;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct FramebufferTarget(gl::GLenum);
+
+pub const FRAMEBUFFER: FramebufferTarget = FramebufferTarget(gl::FRAMEBUFFER);
+pub const READ_FRAMEBUFFER: FramebufferTarget = FramebufferTarget(gl::READ_FRAMEBUFFER);
+pub const DRAW_FRAMEBUFFER: FramebufferTarget = FramebufferTarget(gl::DRAW_FRAMEBUFFER);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct Attachment(gl::GLenum);
+
+pub const DEPTH_ATTACHMENT: Attachment = Attachment(gl::DEPTH_ATTACHMENT);
+pub const STENCIL_ATTACHMENT: Attachment = Attachment(gl::STENCIL_ATTACHMENT);
+pub const DEPTH_STENCIL_ATTACHMENT: Attachment = Attachment(gl::DEPTH_STENCIL_ATTACHMENT);
+
+#[must_use]
+pub fn color_attachment(index: u32) -> Attachment {
+    Attachment(gl::COLOR_ATTACHMENT0 + index)
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct RenderbufferFormat(gl::GLenum);
+
+pub const RGBA8: RenderbufferFormat = RenderbufferFormat(gl::RGBA8);
+pub const SRGB8_ALPHA8: RenderbufferFormat = RenderbufferFormat(gl::SRGB8_ALPHA8);
+pub const DEPTH_COMPONENT24: RenderbufferFormat = RenderbufferFormat(gl::DEPTH_COMPONENT24);
+pub const DEPTH24_STENCIL8: RenderbufferFormat = RenderbufferFormat(gl::DEPTH24_STENCIL8);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct PixelFormat(gl::GLenum);
+
+pub const RED: PixelFormat = PixelFormat(gl::RED);
+pub const RG: PixelFormat = PixelFormat(gl::RG);
+pub const RGB: PixelFormat = PixelFormat(gl::RGB);
+pub const RGBA: PixelFormat = PixelFormat(gl::RGBA);
+pub const RGBA_INTEGER: PixelFormat = PixelFormat(gl::RGBA_INTEGER);
+pub const DEPTH_COMPONENT: PixelFormat = PixelFormat(gl::DEPTH_COMPONENT);
+
+impl PixelFormat {
+    #[must_use]
+    pub fn components(self) -> usize {
+        match self.0 {
+            gl::RED | gl::DEPTH_COMPONENT => 1,
+            gl::RG => 2,
+            gl::RGB => 3,
+            _ => 4,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct PixelType(gl::GLenum);
+
+pub const UNSIGNED_BYTE: PixelType = PixelType(gl::UNSIGNED_BYTE);
+pub const UNSIGNED_INT: PixelType = PixelType(gl::UNSIGNED_INT);
+pub const HALF_FLOAT: PixelType = PixelType(gl::HALF_FLOAT);
+pub const FLOAT: PixelType = PixelType(gl::FLOAT);
+
+impl PixelType {
+    #[must_use]
+    pub fn component_size(self) -> usize {
+        match self.0 {
+            gl::UNSIGNED_BYTE => 1,
+            gl::HALF_FLOAT => 2,
+            _ => 4,
+        }
+    }
+}
+
+/// Size in bytes of a tightly packed image as returned by the read functions
+#[must_use]
+pub fn pixel_data_size(size: (usize, usize), format: PixelFormat, pixel_type: PixelType) -> usize {
+    size.0 * size.1 * format.components() * pixel_type.component_size()
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FramebufferStatus {
+    Complete,
+    IncompleteAttachment,
+    MissingAttachment,
+    IncompleteMultisample,
+    Unsupported,
+    Other(gl::GLenum),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct FramebufferId(gl::GLuint);
+
+/// The default framebuffer of the context, e.g. the window surface
+pub const DEFAULT_FRAMEBUFFER: FramebufferId = FramebufferId(0);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct RenderbufferId(gl::GLuint);
+
+pub const NO_RENDERBUFFER: RenderbufferId = RenderbufferId(0);
+
+#[must_use]
+pub fn gen_framebuffer() -> FramebufferId {
+    let mut framebuffer_id: gl::GLuint = 0;
+    unsafe { gl::GenFramebuffers(1, &mut framebuffer_id) };
+    FramebufferId(framebuffer_id)
+}
+
+pub fn bind_framebuffer(target: FramebufferTarget, framebuffer_id: FramebufferId) {
+    unsafe { gl::BindFramebuffer(target.raw_handle(), framebuffer_id.raw_handle()) };
+}
+
+pub fn delete_framebuffer(framebuffer_id: &mut FramebufferId) {
+    unsafe { gl::DeleteFramebuffers(1, &framebuffer_id.raw_handle()) };
+    framebuffer_id.0 = 0;
+}
+
+#[must_use]
+pub fn gen_renderbuffer() -> RenderbufferId {
+    let mut renderbuffer_id: gl::GLuint = 0;
+    unsafe { gl::GenRenderbuffers(1, &mut renderbuffer_id) };
+    RenderbufferId(renderbuffer_id)
+}
+
+pub fn bind_renderbuffer(renderbuffer_id: RenderbufferId) {
+    unsafe { gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer_id.raw_handle()) };
+}
+
+/// Allocates storage for the bound renderbuffer
+pub fn renderbuffer_storage(format: RenderbufferFormat, size: (usize, usize)) {
+    unsafe { gl::RenderbufferStorage(gl::RENDERBUFFER, format.raw_handle(), size.0 as _, size.1 as _) };
+}
+
+pub fn delete_renderbuffer(renderbuffer_id: &mut RenderbufferId) {
+    unsafe { gl::DeleteRenderbuffers(1, &renderbuffer_id.raw_handle()) };
+    renderbuffer_id.0 = 0;
+}
+
+pub fn framebuffer_renderbuffer(target: FramebufferTarget, attachment: Attachment, renderbuffer_id: RenderbufferId) {
+    unsafe {
+        gl::FramebufferRenderbuffer(
+            target.raw_handle(),
+            attachment.raw_handle(),
+            gl::RENDERBUFFER,
+            renderbuffer_id.raw_handle())
+    };
+}
+
+/// Attaches the base level of the 2D texture `texture_id`
+pub fn framebuffer_texture_2d(target: FramebufferTarget, attachment: Attachment, texture_id: TextureId) {
+    unsafe {
+        gl::FramebufferTexture2D(
+            target.raw_handle(),
+            attachment.raw_handle(),
+            gl::TEXTURE_2D,
+            texture_id.raw_handle(),
+            0)
+    };
+}
+
+#[must_use]
+pub fn check_framebuffer_status(target: FramebufferTarget) -> FramebufferStatus {
+    let status = unsafe { gl::CheckFramebufferStatus(target.raw_handle()) };
+    match status {
+        gl::FRAMEBUFFER_COMPLETE => FramebufferStatus::Complete,
+        gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => FramebufferStatus::IncompleteAttachment,
+        gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => FramebufferStatus::MissingAttachment,
+        gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => FramebufferStatus::IncompleteMultisample,
+        gl::FRAMEBUFFER_UNSUPPORTED => FramebufferStatus::Unsupported,
+        other => FramebufferStatus::Other(other),
+    }
+}
+
+/// Reads a rectangle of the bound read framebuffer into `data`, tightly packed and starting with the bottom row.
+///
+/// # Panics
+/// - `data` is smaller than [`pixel_data_size`]
+pub fn read_pixels<T: Pod>(pos: (usize, usize), size: (usize, usize), format: PixelFormat, pixel_type: PixelType, data: &mut [T]) {
+    assert!(
+        std::mem::size_of_val(data) >= pixel_data_size(size, format, pixel_type),
+        "Pixel buffer too small for a {}x{} read", size.0, size.1);
+    with_tight_packing(|| unsafe {
+        gl::ReadPixels(
+            pos.0 as _,
+            pos.1 as _,
+            size.0 as _,
+            size.1 as _,
+            format.raw_handle(),
+            pixel_type.raw_handle(),
+            data.as_mut_ptr().cast())
+    });
+}
+
+/// Like [`read_pixels`], but writes to the buffer bound to `PIXEL_PACK_BUFFER` starting at byte `offset`.
+/// The call returns without waiting for rendering to finish, the data is available once a later fence got signaled.
+pub fn read_pixels_to_buffer(pos: (usize, usize), size: (usize, usize), format: PixelFormat, pixel_type: PixelType, offset: usize) {
+    with_tight_packing(|| unsafe {
+        gl::ReadPixels(
+            pos.0 as _,
+            pos.1 as _,
+            size.0 as _,
+            size.1 as _,
+            format.raw_handle(),
+            pixel_type.raw_handle(),
+            offset as *mut _)
+    });
+}
+
+/// Runs `read` with a `PACK_ALIGNMENT` of 1, restoring the alignment other code may rely on afterwards
+fn with_tight_packing<F: FnOnce()>(read: F) {
+    let mut alignment: gl::GLint = 0;
+    unsafe { gl::GetIntegerv(gl::PACK_ALIGNMENT, &mut alignment) };
+    if alignment == 1 {
+        read();
+        return;
+    }
+
+    unsafe { gl::PixelStorei(gl::PACK_ALIGNMENT, 1) };
+    read();
+    unsafe { gl::PixelStorei(gl::PACK_ALIGNMENT, alignment) };
+}
+
+/// Reverses the order of the rows of `row_len` elements each, converting between GL's bottom-up and top-down images
+pub fn flip_vertically<T: Copy>(data: &mut [T], row_len: usize) {
+    if row_len == 0 {
+        return;
+    }
+
+    let rows = data.len() / row_len;
+    for row in 0..rows / 2 {
+        let (upper, lower) = data.split_at_mut((rows - 1 - row) * row_len);
+        upper[row * row_len..(row + 1) * row_len].swap_with_slice(&mut lower[..row_len]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::flip_vertically;
+
+    #[test]
+    fn flips_even_row_count() {
+        let mut data = [1, 2, 3, 4, 5, 6, 7, 8];
+        flip_vertically(&mut data, 2);
+        assert_eq!(data, [7, 8, 5, 6, 3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn keeps_middle_row_of_odd_row_count() {
+        let mut data = [1, 2, 3, 4, 5, 6];
+        flip_vertically(&mut data, 2);
+        assert_eq!(data, [5, 6, 3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn ignores_trailing_partial_row() {
+        let mut data = [1, 2, 3, 4, 5];
+        flip_vertically(&mut data, 2);
+        assert_eq!(data, [3, 4, 1, 2, 5]);
+    }
+
+    #[test]
+    fn ignores_empty_rows() {
+        let mut data = [1, 2, 3];
+        flip_vertically(&mut data, 0);
+        assert_eq!(data, [1, 2, 3]);
+
+        let mut empty: [u8; 0] = [];
+        flip_vertically(&mut empty, 4);
+    }
+}
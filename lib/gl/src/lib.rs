@@ -1,4 +1,5 @@
 extern crate gl_raw_handle_derive;
+extern crate bytemuck;
 
 pub use sys::load_with;
 
@@ -10,14 +11,42 @@ pub mod vertex_attrib;
 pub mod rendering;
 pub mod state;
 pub mod capabilities;
+pub mod sync;
+pub mod query;
+pub mod pipeline;
+pub mod texture;
+pub mod framebuffer;
+pub mod state_cache;
+pub mod state_snapshot;
+pub mod context_info;
+pub mod context;
 
 mod gl {
     pub use sys::*;
     pub use sys::types::*;
 }
 
+/// Raw bindings, whose functions call through the [`Context`](::context::Context) current on the calling thread,
+/// or through the process-wide function pointers loaded by [`load_with`] if there is none
 pub mod sys {
-    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+    #[allow(clippy::all)]
+    mod global {
+        include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+    }
+
+    pub use self::global::*;
+    // Functions defined here take precedence over the ones of the glob import
+    include!(concat!(env!("OUT_DIR"), "/dispatch_bindings.rs"));
+
+    /// Bindings whose function pointers are owned by a [`Gl`](struct_bindings::Gl) struct, see [`Context`](::context::Context)
+    #[allow(clippy::all)]
+    pub mod struct_bindings {
+        include!(concat!(env!("OUT_DIR"), "/struct_bindings.rs"));
+
+        // The generator names the struct after the API
+        #[cfg(gl_es)]
+        pub use self::Gles2 as Gl;
+    }
 
     pub trait RawHandle<T> {
         unsafe fn raw_handle(&self) -> T;
@@ -0,0 +1,315 @@
+use std::cell::RefCell;
+
+use capabilities::{self, Capability};
+use program::{self, ProgramId};
+use state::{self, BlendEquation, BlendFuncFactor, CompareFunc, Face, FrontFace, StencilOp};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BlendState {
+    pub enabled: bool,
+    pub equation_rgb: BlendEquation,
+    pub equation_alpha: BlendEquation,
+    pub source_rgb: BlendFuncFactor,
+    pub destination_rgb: BlendFuncFactor,
+    pub source_alpha: BlendFuncFactor,
+    pub destination_alpha: BlendFuncFactor,
+}
+
+impl BlendState {
+    pub const DISABLED: Self = Self {
+        enabled: false,
+        equation_rgb: state::FUNC_ADD,
+        equation_alpha: state::FUNC_ADD,
+        source_rgb: state::ONE,
+        destination_rgb: state::ZERO,
+        source_alpha: state::ONE,
+        destination_alpha: state::ZERO,
+    };
+
+    pub const ALPHA_BLENDING: Self = Self {
+        enabled: true,
+        equation_rgb: state::FUNC_ADD,
+        equation_alpha: state::FUNC_ADD,
+        source_rgb: state::SRC_ALPHA,
+        destination_rgb: state::ONE_MINUS_SRC_ALPHA,
+        source_alpha: state::ONE,
+        destination_alpha: state::ONE_MINUS_SRC_ALPHA,
+    };
+}
+
+impl Default for BlendState {
+    fn default() -> Self { Self::DISABLED }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DepthState {
+    pub test_enabled: bool,
+    pub func: CompareFunc,
+    pub write_enabled: bool,
+}
+
+impl DepthState {
+    pub const DISABLED: Self = Self { test_enabled: false, func: state::LESS, write_enabled: true };
+    pub const LESS_EQUAL: Self = Self { test_enabled: true, func: state::LEQUAL, write_enabled: true };
+}
+
+impl Default for DepthState {
+    fn default() -> Self { Self::DISABLED }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct StencilFaceState {
+    pub func: CompareFunc,
+    pub reference: i32,
+    pub read_mask: u32,
+    pub write_mask: u32,
+    pub stencil_fail: StencilOp,
+    pub depth_fail: StencilOp,
+    pub depth_pass: StencilOp,
+}
+
+impl Default for StencilFaceState {
+    fn default() -> Self {
+        Self {
+            func: state::ALWAYS,
+            reference: 0,
+            read_mask: !0,
+            write_mask: !0,
+            stencil_fail: state::STENCIL_KEEP,
+            depth_fail: state::STENCIL_KEEP,
+            depth_pass: state::STENCIL_KEEP,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct StencilState {
+    pub enabled: bool,
+    pub front: StencilFaceState,
+    pub back: StencilFaceState,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RasterizerState {
+    pub cull_enabled: bool,
+    pub cull_face: Face,
+    pub front_face: FrontFace,
+    pub scissor_enabled: bool,
+    /// Factor and units of the polygon offset, which is disabled if `None`
+    pub polygon_offset: Option<(f32, f32)>,
+    #[cfg(not(gl_es))]
+    pub polygon_mode: state::PolygonMode,
+}
+
+impl Default for RasterizerState {
+    fn default() -> Self {
+        Self {
+            cull_enabled: false,
+            cull_face: state::BACK,
+            front_face: state::CCW,
+            scissor_enabled: false,
+            polygon_offset: None,
+            #[cfg(not(gl_es))]
+            polygon_mode: state::FILL,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ColorMask {
+    pub red: bool,
+    pub green: bool,
+    pub blue: bool,
+    pub alpha: bool,
+}
+
+impl ColorMask {
+    pub const ALL: Self = Self { red: true, green: true, blue: true, alpha: true };
+    pub const NONE: Self = Self { red: false, green: false, blue: false, alpha: false };
+}
+
+impl Default for ColorMask {
+    fn default() -> Self { Self::ALL }
+}
+
+/// Immutable bundle of the fixed-function state and program used by a render pass.
+///
+/// Applying a pipeline state only issues the GL calls for values differing from the previously applied one.
+/// Code changing any of this state through other means must call [`invalidate_pipeline_state`] afterwards.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PipelineState {
+    pub program: ProgramId,
+    pub blend: BlendState,
+    pub depth: DepthState,
+    pub stencil: StencilState,
+    pub rasterizer: RasterizerState,
+    pub color_mask: ColorMask,
+}
+
+impl PipelineState {
+    #[must_use]
+    pub fn new(program: ProgramId) -> Self {
+        Self {
+            program,
+            blend: BlendState::default(),
+            depth: DepthState::default(),
+            stencil: StencilState::default(),
+            rasterizer: RasterizerState::default(),
+            color_mask: ColorMask::default(),
+        }
+    }
+
+    pub fn apply(&self) {
+        CURRENT_PIPELINE_STATE.with(|current| {
+            let mut current = current.borrow_mut();
+            match *current {
+                Some(ref current) => self.apply_diff(current),
+                None => self.apply_all(),
+            }
+            *current = Some(*self);
+        });
+    }
+
+    fn apply_all(&self) {
+        program::use_program(self.program);
+        apply_blend(&self.blend);
+        apply_depth(&self.depth);
+        apply_stencil(&self.stencil);
+        apply_rasterizer(&self.rasterizer);
+        apply_color_mask(&self.color_mask);
+    }
+
+    fn apply_diff(&self, current: &Self) {
+        if self.program != current.program {
+            program::use_program(self.program);
+        }
+
+        if self.blend != current.blend {
+            if self.blend.enabled != current.blend.enabled {
+                set_capability(capabilities::BLEND, self.blend.enabled);
+            }
+            if self.blend.enabled {
+                apply_blend_funcs(&self.blend);
+            }
+        }
+
+        if self.depth != current.depth {
+            if self.depth.test_enabled != current.depth.test_enabled {
+                set_capability(capabilities::DEPTH_TEST, self.depth.test_enabled);
+            }
+            if self.depth.func != current.depth.func {
+                state::depth_func(self.depth.func);
+            }
+            if self.depth.write_enabled != current.depth.write_enabled {
+                state::depth_mask(self.depth.write_enabled);
+            }
+        }
+
+        if self.stencil != current.stencil {
+            if self.stencil.enabled != current.stencil.enabled {
+                set_capability(capabilities::STENCIL_TEST, self.stencil.enabled);
+            }
+            if self.stencil.front != current.stencil.front {
+                apply_stencil_face(state::FRONT, &self.stencil.front);
+            }
+            if self.stencil.back != current.stencil.back {
+                apply_stencil_face(state::BACK, &self.stencil.back);
+            }
+        }
+
+        let rasterizer = &self.rasterizer;
+        let current_rasterizer = &current.rasterizer;
+        if rasterizer.cull_enabled != current_rasterizer.cull_enabled {
+            set_capability(capabilities::CULL_FACE, rasterizer.cull_enabled);
+        }
+        if rasterizer.cull_face != current_rasterizer.cull_face {
+            state::cull_face(rasterizer.cull_face);
+        }
+        if rasterizer.front_face != current_rasterizer.front_face {
+            state::front_face(rasterizer.front_face);
+        }
+        if rasterizer.scissor_enabled != current_rasterizer.scissor_enabled {
+            set_capability(capabilities::SCISSOR_TEST, rasterizer.scissor_enabled);
+        }
+        if rasterizer.polygon_offset != current_rasterizer.polygon_offset {
+            apply_polygon_offset(rasterizer.polygon_offset);
+        }
+        #[cfg(not(gl_es))]
+        {
+            if rasterizer.polygon_mode != current_rasterizer.polygon_mode {
+                state::polygon_mode(state::FRONT_AND_BACK, rasterizer.polygon_mode);
+            }
+        }
+
+        if self.color_mask != current.color_mask {
+            apply_color_mask(&self.color_mask);
+        }
+    }
+}
+
+thread_local!(static CURRENT_PIPELINE_STATE: RefCell<Option<PipelineState>> = const { RefCell::new(None) });
+
+/// Forgets the tracked pipeline state, so the next [`PipelineState::apply`] issues every call
+pub fn invalidate_pipeline_state() {
+    CURRENT_PIPELINE_STATE.with(|current| *current.borrow_mut() = None);
+}
+
+fn set_capability(capability: Capability, enabled: bool) {
+    if enabled {
+        capabilities::enable(capability);
+    } else {
+        capabilities::disable(capability);
+    }
+}
+
+fn apply_blend(blend: &BlendState) {
+    set_capability(capabilities::BLEND, blend.enabled);
+    apply_blend_funcs(blend);
+}
+
+fn apply_blend_funcs(blend: &BlendState) {
+    state::blend_equation_separate(blend.equation_rgb, blend.equation_alpha);
+    state::blend_func_separate(blend.source_rgb, blend.destination_rgb, blend.source_alpha, blend.destination_alpha);
+}
+
+fn apply_depth(depth: &DepthState) {
+    set_capability(capabilities::DEPTH_TEST, depth.test_enabled);
+    state::depth_func(depth.func);
+    state::depth_mask(depth.write_enabled);
+}
+
+fn apply_stencil(stencil: &StencilState) {
+    set_capability(capabilities::STENCIL_TEST, stencil.enabled);
+    apply_stencil_face(state::FRONT, &stencil.front);
+    apply_stencil_face(state::BACK, &stencil.back);
+}
+
+fn apply_stencil_face(face: Face, stencil_face: &StencilFaceState) {
+    state::stencil_func_separate(face, stencil_face.func, stencil_face.reference, stencil_face.read_mask);
+    state::stencil_mask_separate(face, stencil_face.write_mask);
+    state::stencil_op_separate(face, stencil_face.stencil_fail, stencil_face.depth_fail, stencil_face.depth_pass);
+}
+
+fn apply_rasterizer(rasterizer: &RasterizerState) {
+    set_capability(capabilities::CULL_FACE, rasterizer.cull_enabled);
+    state::cull_face(rasterizer.cull_face);
+    state::front_face(rasterizer.front_face);
+    set_capability(capabilities::SCISSOR_TEST, rasterizer.scissor_enabled);
+    apply_polygon_offset(rasterizer.polygon_offset);
+    #[cfg(not(gl_es))]
+    state::polygon_mode(state::FRONT_AND_BACK, rasterizer.polygon_mode);
+}
+
+fn apply_polygon_offset(polygon_offset: Option<(f32, f32)>) {
+    match polygon_offset {
+        Some((factor, units)) => {
+            capabilities::enable(capabilities::POLYGON_OFFSET_FILL);
+            state::polygon_offset(factor, units);
+        }
+        None => capabilities::disable(capabilities::POLYGON_OFFSET_FILL),
+    }
+}
+
+fn apply_color_mask(color_mask: &ColorMask) {
+    state::color_mask(color_mask.red, color_mask.green, color_mask.blue, color_mask.alpha);
+}
@@ -2,6 +2,7 @@ use shader::ShaderId;
 
 use ::{gl, gl::RawHandle};
 use gl_raw_handle_derive::RawHandle;
+use state_cache;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
 pub struct ProgramId(gl::GLuint);
@@ -19,6 +20,7 @@ pub fn create_program() -> ProgramId {
 
 pub fn delete_program(program_id: &mut ProgramId) {
     unsafe { gl::DeleteProgram(program_id.raw_handle()) };
+    state_cache::forget_program(program_id.0);
     program_id.0 = 0;
 }
 
@@ -66,7 +68,9 @@ pub fn program_info_log(program_id: ProgramId) -> Option<String> {
 }
 
 pub fn use_program(program_id: ProgramId) {
-    unsafe { gl::UseProgram(program_id.raw_handle()); }
+    if state_cache::use_program_required(program_id.0) {
+        unsafe { gl::UseProgram(program_id.raw_handle()); }
+    }
 }
 
 pub fn uniform_location<T: Into<String>>(program_id: ProgramId, name: T) -> UniformLocation {
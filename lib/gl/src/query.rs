@@ -0,0 +1,117 @@
+use ::{gl, gl::RawHandle};
+use context_info;
+use gl_raw_handle_derive::RawHandle;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct QueryTarget(gl::GLenum);
+
+pub const ANY_SAMPLES_PASSED: QueryTarget = QueryTarget(gl::ANY_SAMPLES_PASSED);
+pub const ANY_SAMPLES_PASSED_CONSERVATIVE: QueryTarget = QueryTarget(gl::ANY_SAMPLES_PASSED_CONSERVATIVE);
+pub const TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN: QueryTarget = QueryTarget(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN);
+#[cfg(not(gl_es))]
+pub const SAMPLES_PASSED: QueryTarget = QueryTarget(gl::SAMPLES_PASSED);
+#[cfg(not(gl_es))]
+pub const PRIMITIVES_GENERATED: QueryTarget = QueryTarget(gl::PRIMITIVES_GENERATED);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct QueryId(gl::GLuint);
+
+pub const NO_QUERY: QueryId = QueryId(0);
+
+#[must_use]
+pub fn gen_queries(count: usize) -> Vec<QueryId> {
+    let mut raw_query_ids: Vec<gl::GLuint> = Vec::with_capacity(count);
+
+    unsafe {
+        let raw_query_ids_ptr = raw_query_ids.spare_capacity_mut().as_mut_ptr().cast();
+        gl::GenQueries(count as gl::GLsizei, raw_query_ids_ptr);
+        raw_query_ids.set_len(count);
+    }
+
+    raw_query_ids.into_iter()
+        .map(QueryId)
+        .collect()
+}
+
+#[must_use]
+pub fn gen_query() -> QueryId {
+    let mut query_id: gl::GLuint = 0;
+    unsafe { gl::GenQueries(1, &mut query_id) };
+    QueryId(query_id)
+}
+
+pub fn delete_query(query_id: &mut QueryId) {
+    unsafe { gl::DeleteQueries(1, &query_id.raw_handle()) };
+    query_id.0 = 0;
+}
+
+pub fn begin_query(target: QueryTarget, query_id: QueryId) {
+    unsafe { gl::BeginQuery(target.raw_handle(), query_id.raw_handle()) };
+}
+
+pub fn end_query(target: QueryTarget) {
+    unsafe { gl::EndQuery(target.raw_handle()) };
+}
+
+/// Records the GPU timestamp in nanoseconds once all previously issued commands completed
+#[cfg(any(not(gl_es), gl_extension = "GL_EXT_disjoint_timer_query"))]
+pub fn query_timestamp(query_id: QueryId) {
+    #[cfg(not(gl_es))]
+    unsafe { gl::QueryCounter(query_id.raw_handle(), gl::TIMESTAMP) };
+    #[cfg(gl_es)]
+    unsafe { gl::QueryCounterEXT(query_id.raw_handle(), gl::TIMESTAMP_EXT) };
+}
+
+#[must_use]
+pub fn query_result_available(query_id: QueryId) -> bool {
+    let mut available: gl::GLuint = 0;
+    unsafe { gl::GetQueryObjectuiv(query_id.raw_handle(), gl::QUERY_RESULT_AVAILABLE, &mut available) };
+    available != 0
+}
+
+/// Blocks until the result is available, check [`query_result_available`] beforehand to avoid stalling
+#[must_use]
+pub fn query_result(query_id: QueryId) -> u32 {
+    let mut result: gl::GLuint = 0;
+    unsafe { gl::GetQueryObjectuiv(query_id.raw_handle(), gl::QUERY_RESULT, &mut result) };
+    result
+}
+
+/// Blocks until the result is available, check [`query_result_available`] beforehand to avoid stalling
+#[cfg(any(not(gl_es), gl_extension = "GL_EXT_disjoint_timer_query"))]
+#[must_use]
+pub fn query_result_u64(query_id: QueryId) -> u64 {
+    let mut result: gl::GLuint64 = 0;
+    #[cfg(not(gl_es))]
+    unsafe { gl::GetQueryObjectui64v(query_id.raw_handle(), gl::QUERY_RESULT, &mut result) };
+    #[cfg(gl_es)]
+    unsafe { gl::GetQueryObjectui64vEXT(query_id.raw_handle(), gl::QUERY_RESULT, &mut result) };
+    result
+}
+
+/// Whether timer queries can be used, which on GLES depends on `GL_EXT_disjoint_timer_query`
+#[must_use]
+pub fn timer_queries_supported() -> bool {
+    if cfg!(not(gl_es)) {
+        true
+    } else if cfg!(gl_extension = "GL_EXT_disjoint_timer_query") {
+        context_info::has_extension("GL_EXT_disjoint_timer_query")
+    } else {
+        false
+    }
+}
+
+/// Returns whether a GPU disjoint event, e.g. a frequency change, invalidated timer queries in flight.
+/// Reading the flag resets it.
+#[cfg(any(not(gl_es), gl_extension = "GL_EXT_disjoint_timer_query"))]
+#[must_use]
+pub fn gpu_disjoint() -> bool {
+    #[cfg(not(gl_es))]
+    return false;
+    #[cfg(gl_es)]
+    {
+        let mut disjoint: gl::GLint = 0;
+        unsafe { gl::GetIntegerv(gl::GPU_DISJOINT_EXT, &mut disjoint) };
+        disjoint != 0
+    }
+}
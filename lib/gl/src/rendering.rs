@@ -41,3 +41,8 @@ pub fn clear_color(color_argb: u32) {
 pub fn draw_arrays(draw_mode: DrawMode, start_index: usize, count: usize) {
     unsafe { gl::DrawArrays(draw_mode.raw_handle(), start_index as _, count as _) };
 }
+
+/// Blocks until all previously issued commands have completed
+pub fn finish() {
+    unsafe { gl::Finish() };
+}
@@ -6,13 +6,13 @@ pub struct ShaderKind(gl::GLenum);
 
 pub const VERTEX_SHADER: ShaderKind = ShaderKind(gl::VERTEX_SHADER);
 pub const FRAGMENT_SHADER: ShaderKind = ShaderKind(gl::FRAGMENT_SHADER);
-#[cfg(not(target_os = "emscripten"))]
+#[cfg(not(gl_es))]
 pub const GEOMETRY_SHADER: ShaderKind = ShaderKind(gl::GEOMETRY_SHADER);
-#[cfg(not(target_os = "emscripten"))]
+#[cfg(not(gl_es))]
 pub const TESSELLATION_CONTROL_SHADER: ShaderKind = ShaderKind(gl::TESS_CONTROL_SHADER);
-#[cfg(not(target_os = "emscripten"))]
+#[cfg(not(gl_es))]
 pub const TESSELLATION_EVALUATION_SHADER: ShaderKind = ShaderKind(gl::TESS_EVALUATION_SHADER);
-#[cfg(not(target_os = "emscripten"))]
+#[cfg(not(gl_es))]
 pub const COMPUTE_SHADER: ShaderKind = ShaderKind(gl::COMPUTE_SHADER);
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
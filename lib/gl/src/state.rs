@@ -61,3 +61,125 @@ pub fn blend_func_separate(
         )
     };
 }
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct CompareFunc(gl::GLenum);
+
+pub const NEVER: CompareFunc = CompareFunc(gl::NEVER);
+pub const LESS: CompareFunc = CompareFunc(gl::LESS);
+pub const EQUAL: CompareFunc = CompareFunc(gl::EQUAL);
+pub const LEQUAL: CompareFunc = CompareFunc(gl::LEQUAL);
+pub const GREATER: CompareFunc = CompareFunc(gl::GREATER);
+pub const NOTEQUAL: CompareFunc = CompareFunc(gl::NOTEQUAL);
+pub const GEQUAL: CompareFunc = CompareFunc(gl::GEQUAL);
+pub const ALWAYS: CompareFunc = CompareFunc(gl::ALWAYS);
+
+pub fn depth_func(compare_func: CompareFunc) {
+    unsafe { gl::DepthFunc(compare_func.raw_handle()) };
+}
+
+pub fn depth_mask(write_enabled: bool) {
+    unsafe { gl::DepthMask(if write_enabled { gl::TRUE } else { gl::FALSE }) };
+}
+
+pub fn depth_range(near: f32, far: f32) {
+    unsafe { gl::DepthRangef(near, far) };
+}
+
+pub fn clear_depth(depth: f32) {
+    unsafe { gl::ClearDepthf(depth) };
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct Face(gl::GLenum);
+
+pub const FRONT: Face = Face(gl::FRONT);
+pub const BACK: Face = Face(gl::BACK);
+pub const FRONT_AND_BACK: Face = Face(gl::FRONT_AND_BACK);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct StencilOp(gl::GLenum);
+
+pub const STENCIL_KEEP: StencilOp = StencilOp(gl::KEEP);
+pub const STENCIL_ZERO: StencilOp = StencilOp(gl::ZERO);
+pub const STENCIL_REPLACE: StencilOp = StencilOp(gl::REPLACE);
+pub const STENCIL_INCR: StencilOp = StencilOp(gl::INCR);
+pub const STENCIL_INCR_WRAP: StencilOp = StencilOp(gl::INCR_WRAP);
+pub const STENCIL_DECR: StencilOp = StencilOp(gl::DECR);
+pub const STENCIL_DECR_WRAP: StencilOp = StencilOp(gl::DECR_WRAP);
+pub const STENCIL_INVERT: StencilOp = StencilOp(gl::INVERT);
+
+pub fn stencil_func(compare_func: CompareFunc, reference: i32, mask: u32) {
+    unsafe { gl::StencilFunc(compare_func.raw_handle(), reference, mask) };
+}
+
+pub fn stencil_func_separate(face: Face, compare_func: CompareFunc, reference: i32, mask: u32) {
+    unsafe { gl::StencilFuncSeparate(face.raw_handle(), compare_func.raw_handle(), reference, mask) };
+}
+
+pub fn stencil_op(stencil_fail: StencilOp, depth_fail: StencilOp, depth_pass: StencilOp) {
+    unsafe { gl::StencilOp(stencil_fail.raw_handle(), depth_fail.raw_handle(), depth_pass.raw_handle()) };
+}
+
+pub fn stencil_op_separate(face: Face, stencil_fail: StencilOp, depth_fail: StencilOp, depth_pass: StencilOp) {
+    unsafe {
+        gl::StencilOpSeparate(
+            face.raw_handle(),
+            stencil_fail.raw_handle(),
+            depth_fail.raw_handle(),
+            depth_pass.raw_handle(),
+        )
+    };
+}
+
+pub fn stencil_mask(mask: u32) {
+    unsafe { gl::StencilMask(mask) };
+}
+
+pub fn stencil_mask_separate(face: Face, mask: u32) {
+    unsafe { gl::StencilMaskSeparate(face.raw_handle(), mask) };
+}
+
+pub fn cull_face(face: Face) {
+    unsafe { gl::CullFace(face.raw_handle()) };
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct FrontFace(gl::GLenum);
+
+pub const CW: FrontFace = FrontFace(gl::CW);
+pub const CCW: FrontFace = FrontFace(gl::CCW);
+
+pub fn front_face(front_face: FrontFace) {
+    unsafe { gl::FrontFace(front_face.raw_handle()) };
+}
+
+pub fn color_mask(red: bool, green: bool, blue: bool, alpha: bool) {
+    let to_gl = |enabled: bool| if enabled { gl::TRUE } else { gl::FALSE };
+    unsafe { gl::ColorMask(to_gl(red), to_gl(green), to_gl(blue), to_gl(alpha)) };
+}
+
+pub fn polygon_offset(factor: f32, units: f32) {
+    unsafe { gl::PolygonOffset(factor, units) };
+}
+
+pub fn line_width(width: f32) {
+    unsafe { gl::LineWidth(width) };
+}
+
+#[cfg(not(gl_es))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct PolygonMode(gl::GLenum);
+
+#[cfg(not(gl_es))]
+pub const POINT: PolygonMode = PolygonMode(gl::POINT);
+#[cfg(not(gl_es))]
+pub const LINE: PolygonMode = PolygonMode(gl::LINE);
+#[cfg(not(gl_es))]
+pub const FILL: PolygonMode = PolygonMode(gl::FILL);
+
+/// Core profiles only accept [`FRONT_AND_BACK`] as face
+#[cfg(not(gl_es))]
+pub fn polygon_mode(face: Face, polygon_mode: PolygonMode) {
+    unsafe { gl::PolygonMode(face.raw_handle(), polygon_mode.raw_handle()) };
+}
@@ -0,0 +1,168 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use gl;
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct StateCacheStats {
+    pub calls_issued: u64,
+    pub calls_skipped: u64,
+}
+
+/// Shadow copy of the bindings and capabilities set through this crate, used to skip redundant calls
+#[derive(Debug, Default)]
+struct StateCache {
+    enabled: bool,
+    buffer_bindings: HashMap<gl::GLenum, gl::GLuint>,
+    vertex_array: Option<gl::GLuint>,
+    program: Option<gl::GLuint>,
+    capabilities: HashMap<gl::GLenum, bool>,
+    active_texture_unit: Option<gl::GLenum>,
+    texture_bindings: HashMap<(gl::GLenum, gl::GLenum), gl::GLuint>,
+    stats: StateCacheStats,
+}
+
+impl StateCache {
+    fn invalidate(&mut self) {
+        self.buffer_bindings.clear();
+        self.vertex_array = None;
+        self.program = None;
+        self.capabilities.clear();
+        self.active_texture_unit = None;
+        self.texture_bindings.clear();
+    }
+}
+
+thread_local!(static STATE_CACHE: RefCell<StateCache> = RefCell::new(StateCache::default()));
+
+/// Enables or disables skipping of redundant binds, which is disabled by default.
+/// Enabling the cache starts out with no assumptions about the current state.
+pub fn set_state_cache_enabled(enabled: bool) {
+    STATE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        cache.invalidate();
+        cache.enabled = enabled;
+    });
+}
+
+#[must_use]
+pub fn state_cache_enabled() -> bool {
+    STATE_CACHE.with(|cache| cache.borrow().enabled)
+}
+
+/// Forgets all cached state, which is required after changing bindings or capabilities through `sys` directly
+pub fn invalidate() {
+    STATE_CACHE.with(|cache| cache.borrow_mut().invalidate());
+}
+
+#[must_use]
+pub fn state_cache_stats() -> StateCacheStats {
+    STATE_CACHE.with(|cache| cache.borrow().stats)
+}
+
+pub fn reset_state_cache_stats() {
+    STATE_CACHE.with(|cache| cache.borrow_mut().stats = StateCacheStats::default());
+}
+
+/// Runs `update` against the cache if enabled, returning whether the GL call has to be issued
+fn with_cache<F>(update: F) -> bool
+    where
+        F: FnOnce(&mut StateCache) -> bool,
+{
+    STATE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let required = !cache.enabled || update(&mut cache);
+        if required {
+            cache.stats.calls_issued += 1;
+        } else {
+            cache.stats.calls_skipped += 1;
+        }
+        required
+    })
+}
+
+/// Stores `value` under `key`, returning whether it differed from the previous value
+fn replace<K: Hash + Eq, V: PartialEq>(map: &mut HashMap<K, V>, key: K, value: V) -> bool {
+    if map.get(&key) == Some(&value) {
+        false
+    } else {
+        map.insert(key, value);
+        true
+    }
+}
+
+/// Stores `value`, returning whether it differed from the previous value
+fn replace_option<V: PartialEq + Copy>(current: &mut Option<V>, value: V) -> bool {
+    if *current == Some(value) {
+        false
+    } else {
+        *current = Some(value);
+        true
+    }
+}
+
+pub(crate) fn bind_buffer_required(target: gl::GLenum, buffer: gl::GLuint) -> bool {
+    with_cache(|cache| replace(&mut cache.buffer_bindings, target, buffer))
+}
+
+pub(crate) fn bind_vertex_array_required(vertex_array: gl::GLuint) -> bool {
+    with_cache(|cache| {
+        let required = replace_option(&mut cache.vertex_array, vertex_array);
+        if required {
+            // The element array buffer binding is part of the vertex array state
+            cache.buffer_bindings.remove(&gl::ELEMENT_ARRAY_BUFFER);
+        }
+        required
+    })
+}
+
+pub(crate) fn use_program_required(program: gl::GLuint) -> bool {
+    with_cache(|cache| replace_option(&mut cache.program, program))
+}
+
+pub(crate) fn set_capability_required(capability: gl::GLenum, enabled: bool) -> bool {
+    with_cache(|cache| replace(&mut cache.capabilities, capability, enabled))
+}
+
+pub(crate) fn active_texture_required(unit: gl::GLenum) -> bool {
+    with_cache(|cache| replace_option(&mut cache.active_texture_unit, unit))
+}
+
+pub(crate) fn bind_texture_required(target: gl::GLenum, texture: gl::GLuint) -> bool {
+    with_cache(|cache| match cache.active_texture_unit {
+        Some(unit) => replace(&mut cache.texture_bindings, (unit, target), texture),
+        None => true,
+    })
+}
+
+/// Deleted buffers are implicitly unbound from every target
+pub(crate) fn forget_buffer(buffer: gl::GLuint) {
+    STATE_CACHE.with(|cache| cache.borrow_mut().buffer_bindings.retain(|_, bound| *bound != buffer));
+}
+
+/// Deleting the bound vertex array reverts the binding to zero
+pub(crate) fn forget_vertex_array(vertex_array: gl::GLuint) {
+    STATE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.vertex_array == Some(vertex_array) {
+            cache.vertex_array = Some(0);
+            cache.buffer_bindings.remove(&gl::ELEMENT_ARRAY_BUFFER);
+        }
+    });
+}
+
+/// The name of a deleted program may be reused, so the binding is no longer known
+pub(crate) fn forget_program(program: gl::GLuint) {
+    STATE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.program == Some(program) {
+            cache.program = None;
+        }
+    });
+}
+
+/// Deleted textures are implicitly unbound from every unit
+pub(crate) fn forget_texture(texture: gl::GLuint) {
+    STATE_CACHE.with(|cache| cache.borrow_mut().texture_bindings.retain(|_, bound| *bound != texture));
+}
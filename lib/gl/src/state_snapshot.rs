@@ -0,0 +1,154 @@
+use gl;
+use pipeline;
+use state_cache;
+
+/// Capabilities which render passes commonly toggle
+const CAPABILITIES: [gl::GLenum; 6] = [
+    gl::BLEND,
+    gl::CULL_FACE,
+    gl::DEPTH_TEST,
+    gl::STENCIL_TEST,
+    gl::SCISSOR_TEST,
+    gl::POLYGON_OFFSET_FILL,
+];
+
+/// State queried from GL before a render pass, which restores it afterwards for the code around the pass.
+///
+/// Covers bindings, viewport and scissor box, blending, depth, culling, color mask and polygon mode,
+/// but not stencil functions and operations. Only the 2D texture bound to the first unit is captured.
+/// State changed through other means than this crate is captured as well, since every value is queried.
+/// Restoring bypasses the state cache, so it and the tracked pipeline state are invalidated afterwards.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StateSnapshot {
+    program: gl::GLint,
+    vertex_array: gl::GLint,
+    array_buffer: gl::GLint,
+    element_array_buffer: gl::GLint,
+    active_texture: gl::GLint,
+    texture_2d: gl::GLint,
+    viewport: [gl::GLint; 4],
+    scissor_box: [gl::GLint; 4],
+    blend_equation_rgb: gl::GLint,
+    blend_equation_alpha: gl::GLint,
+    blend_source_rgb: gl::GLint,
+    blend_destination_rgb: gl::GLint,
+    blend_source_alpha: gl::GLint,
+    blend_destination_alpha: gl::GLint,
+    depth_func: gl::GLint,
+    depth_write_enabled: bool,
+    cull_face: gl::GLint,
+    front_face: gl::GLint,
+    color_mask: [bool; 4],
+    capabilities: [bool; 6],
+    #[cfg(not(gl_es))]
+    polygon_mode: gl::GLint,
+}
+
+impl StateSnapshot {
+    #[must_use]
+    pub fn capture() -> Self {
+        let active_texture = get_integer(gl::ACTIVE_TEXTURE);
+        unsafe { gl::ActiveTexture(gl::TEXTURE0) };
+        let texture_2d = get_integer(gl::TEXTURE_BINDING_2D);
+        unsafe { gl::ActiveTexture(active_texture as gl::GLenum) };
+
+        let mut capabilities = [false; 6];
+        for (enabled, capability) in capabilities.iter_mut().zip(CAPABILITIES.iter()) {
+            *enabled = unsafe { gl::IsEnabled(*capability) } == gl::TRUE;
+        }
+
+        let mut color_mask = [gl::TRUE; 4];
+        unsafe { gl::GetBooleanv(gl::COLOR_WRITEMASK, color_mask.as_mut_ptr()) };
+        let mut depth_write_enabled = gl::TRUE;
+        unsafe { gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut depth_write_enabled) };
+
+        Self {
+            program: get_integer(gl::CURRENT_PROGRAM),
+            vertex_array: get_integer(gl::VERTEX_ARRAY_BINDING),
+            array_buffer: get_integer(gl::ARRAY_BUFFER_BINDING),
+            element_array_buffer: get_integer(gl::ELEMENT_ARRAY_BUFFER_BINDING),
+            active_texture,
+            texture_2d,
+            viewport: get_integers(gl::VIEWPORT),
+            scissor_box: get_integers(gl::SCISSOR_BOX),
+            blend_equation_rgb: get_integer(gl::BLEND_EQUATION_RGB),
+            blend_equation_alpha: get_integer(gl::BLEND_EQUATION_ALPHA),
+            blend_source_rgb: get_integer(gl::BLEND_SRC_RGB),
+            blend_destination_rgb: get_integer(gl::BLEND_DST_RGB),
+            blend_source_alpha: get_integer(gl::BLEND_SRC_ALPHA),
+            blend_destination_alpha: get_integer(gl::BLEND_DST_ALPHA),
+            depth_func: get_integer(gl::DEPTH_FUNC),
+            depth_write_enabled: depth_write_enabled == gl::TRUE,
+            cull_face: get_integer(gl::CULL_FACE_MODE),
+            front_face: get_integer(gl::FRONT_FACE),
+            color_mask: [
+                color_mask[0] == gl::TRUE,
+                color_mask[1] == gl::TRUE,
+                color_mask[2] == gl::TRUE,
+                color_mask[3] == gl::TRUE,
+            ],
+            capabilities,
+            // Core profiles share one mode between front and back faces
+            #[cfg(not(gl_es))]
+            polygon_mode: get_integers::<[gl::GLint; 2]>(gl::POLYGON_MODE)[0],
+        }
+    }
+
+    pub fn restore(&self) {
+        let to_gl = |enabled: bool| if enabled { gl::TRUE } else { gl::FALSE };
+        unsafe {
+            gl::UseProgram(self.program as gl::GLuint);
+            // The element array buffer binding is part of the vertex array state
+            gl::BindVertexArray(self.vertex_array as gl::GLuint);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.element_array_buffer as gl::GLuint);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.array_buffer as gl::GLuint);
+
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture_2d as gl::GLuint);
+            gl::ActiveTexture(self.active_texture as gl::GLenum);
+
+            let [x, y, width, height] = self.viewport;
+            gl::Viewport(x, y, width, height);
+            let [x, y, width, height] = self.scissor_box;
+            gl::Scissor(x, y, width, height);
+
+            gl::BlendEquationSeparate(self.blend_equation_rgb as gl::GLenum, self.blend_equation_alpha as gl::GLenum);
+            gl::BlendFuncSeparate(
+                self.blend_source_rgb as gl::GLenum,
+                self.blend_destination_rgb as gl::GLenum,
+                self.blend_source_alpha as gl::GLenum,
+                self.blend_destination_alpha as gl::GLenum);
+            gl::DepthFunc(self.depth_func as gl::GLenum);
+            gl::DepthMask(to_gl(self.depth_write_enabled));
+            gl::CullFace(self.cull_face as gl::GLenum);
+            gl::FrontFace(self.front_face as gl::GLenum);
+            let [red, green, blue, alpha] = self.color_mask;
+            gl::ColorMask(to_gl(red), to_gl(green), to_gl(blue), to_gl(alpha));
+            #[cfg(not(gl_es))]
+            gl::PolygonMode(gl::FRONT_AND_BACK, self.polygon_mode as gl::GLenum);
+
+            for (enabled, capability) in self.capabilities.iter().zip(CAPABILITIES.iter()) {
+                if *enabled {
+                    gl::Enable(*capability);
+                } else {
+                    gl::Disable(*capability);
+                }
+            }
+        }
+
+        state_cache::invalidate();
+        pipeline::invalidate_pipeline_state();
+    }
+}
+
+fn get_integer(name: gl::GLenum) -> gl::GLint {
+    let mut value: gl::GLint = 0;
+    unsafe { gl::GetIntegerv(name, &mut value) };
+    value
+}
+
+fn get_integers<T: Default + AsMut<[gl::GLint]>>(name: gl::GLenum) -> T {
+    let mut values = T::default();
+    unsafe { gl::GetIntegerv(name, values.as_mut().as_mut_ptr()) };
+    values
+}
@@ -0,0 +1,108 @@
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use ::{gl, gl::RawHandle};
+use gl_raw_handle_derive::RawHandle;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct SyncId(gl::GLsync);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WaitStatus {
+    /// All commands issued before the fence have completed
+    Signaled,
+    /// The timeout expired before the fence got signaled
+    Timeout,
+    /// Waiting failed, e.g. due to a lost context
+    Failed,
+}
+
+#[must_use]
+pub fn fence_sync() -> SyncId {
+    let sync = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+    SyncId(sync)
+}
+
+/// Blocks the calling thread until the sync object got signaled or `timeout_ns` nanoseconds have passed
+#[must_use]
+pub fn client_wait_sync(sync_id: SyncId, timeout_ns: u64) -> WaitStatus {
+    let result = unsafe { gl::ClientWaitSync(sync_id.raw_handle(), gl::SYNC_FLUSH_COMMANDS_BIT, timeout_ns) };
+    match result {
+        gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => WaitStatus::Signaled,
+        gl::TIMEOUT_EXPIRED => WaitStatus::Timeout,
+        _ => WaitStatus::Failed,
+    }
+}
+
+/// Makes the server wait for the sync object before executing further commands, without blocking the client
+pub fn wait_sync(sync_id: SyncId) {
+    unsafe { gl::WaitSync(sync_id.raw_handle(), 0, gl::TIMEOUT_IGNORED) };
+}
+
+pub fn delete_sync(sync_id: &mut SyncId) {
+    unsafe { gl::DeleteSync(sync_id.raw_handle()) };
+    sync_id.0 = std::ptr::null();
+}
+
+/// Owned fence over all commands issued before its creation, deleted once dropped.
+///
+/// If creating the sync object fails, e.g. because the context is lost, the fence behaves as if it was signaled,
+/// since there is no sync object left to wait on.
+#[derive(Debug)]
+pub struct Fence {
+    id: SyncId,
+}
+
+impl Fence {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { id: fence_sync() }
+    }
+
+    /// Blocks for at most `timeout`, which is clamped to the nanosecond range of `u64`
+    #[must_use]
+    pub fn client_wait(&self, timeout: Duration) -> WaitStatus {
+        if self.is_null() {
+            return WaitStatus::Signaled;
+        }
+
+        let timeout_ns = u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX);
+        client_wait_sync(self.id, timeout_ns)
+    }
+
+    /// Returns whether the fence got signaled without blocking
+    #[must_use]
+    pub fn is_signaled(&self) -> bool {
+        self.client_wait(Duration::ZERO) == WaitStatus::Signaled
+    }
+
+    pub fn wait(&self) {
+        if !self.is_null() {
+            wait_sync(self.id);
+        }
+    }
+
+    /// The null sync object if creating it failed
+    #[must_use]
+    pub fn id(&self) -> SyncId {
+        self.id
+    }
+
+    fn is_null(&self) -> bool {
+        self.id.0.is_null()
+    }
+}
+
+impl Default for Fence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        if !self.is_null() {
+            delete_sync(&mut self.id);
+        }
+    }
+}
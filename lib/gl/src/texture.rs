@@ -0,0 +1,78 @@
+use ::{gl, gl::RawHandle};
+use gl_raw_handle_derive::RawHandle;
+use state_cache;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct TextureTarget(gl::GLenum);
+
+pub const TEXTURE_2D: TextureTarget = TextureTarget(gl::TEXTURE_2D);
+pub const TEXTURE_3D: TextureTarget = TextureTarget(gl::TEXTURE_3D);
+pub const TEXTURE_2D_ARRAY: TextureTarget = TextureTarget(gl::TEXTURE_2D_ARRAY);
+pub const TEXTURE_CUBE_MAP: TextureTarget = TextureTarget(gl::TEXTURE_CUBE_MAP);
+
+/// Sized internal format of texture storage, prefixed to tell it apart from the renderbuffer formats
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct TextureFormat(gl::GLenum);
+
+pub const TEXTURE_RGBA8: TextureFormat = TextureFormat(gl::RGBA8);
+pub const TEXTURE_SRGB8_ALPHA8: TextureFormat = TextureFormat(gl::SRGB8_ALPHA8);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct TextureUnit(gl::GLenum);
+
+#[must_use]
+pub fn texture_unit(index: u32) -> TextureUnit {
+    TextureUnit(gl::TEXTURE0 + index)
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
+pub struct TextureId(gl::GLuint);
+
+pub const NO_TEXTURE: TextureId = TextureId(0);
+
+impl TextureId {
+    /// # Safety
+    /// `handle` must be zero or the name of a texture created in the current context
+    #[must_use]
+    pub const unsafe fn from_raw_handle(handle: gl::GLuint) -> Self {
+        Self(handle)
+    }
+}
+
+#[must_use]
+pub fn gen_texture() -> TextureId {
+    let mut texture_id: gl::GLuint = 0;
+    unsafe { gl::GenTextures(1, &mut texture_id) };
+    TextureId(texture_id)
+}
+
+#[cfg(not(gl_es))]
+#[must_use]
+pub fn create_texture(target: TextureTarget) -> TextureId {
+    let mut texture_id: gl::GLuint = 0;
+    unsafe { gl::CreateTextures(target.raw_handle(), 1, &mut texture_id) };
+    TextureId(texture_id)
+}
+
+pub fn delete_texture(texture_id: &mut TextureId) {
+    unsafe { gl::DeleteTextures(1, &texture_id.raw_handle()) };
+    state_cache::forget_texture(texture_id.0);
+    texture_id.0 = 0;
+}
+
+pub fn active_texture(texture_unit: TextureUnit) {
+    if state_cache::active_texture_required(texture_unit.0) {
+        unsafe { gl::ActiveTexture(texture_unit.raw_handle()) };
+    }
+}
+
+pub fn bind_texture(target: TextureTarget, texture_id: TextureId) {
+    if state_cache::bind_texture_required(target.0, texture_id.0) {
+        unsafe { gl::BindTexture(target.raw_handle(), texture_id.raw_handle()) };
+    }
+}
+
+/// Allocates immutable storage of `levels` mipmap levels for the 2D texture bound to `target`
+pub fn texture_storage_2d(target: TextureTarget, levels: usize, format: TextureFormat, size: (usize, usize)) {
+    unsafe { gl::TexStorage2D(target.raw_handle(), levels as _, format.raw_handle(), size.0 as _, size.1 as _) };
+}
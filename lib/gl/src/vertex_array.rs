@@ -1,5 +1,6 @@
 use ::{gl, gl::RawHandle};
 use gl_raw_handle_derive::RawHandle;
+use state_cache;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, RawHandle)]
 pub struct VertexArrayId(gl::GLuint);
@@ -29,5 +30,13 @@ pub fn gen_vertex_array() -> VertexArrayId {
 }
 
 pub fn bind_vertex_array(vertex_array_id: VertexArrayId) {
-    unsafe { gl::BindVertexArray(vertex_array_id.raw_handle()) };
+    if state_cache::bind_vertex_array_required(vertex_array_id.0) {
+        unsafe { gl::BindVertexArray(vertex_array_id.raw_handle()) };
+    }
+}
+
+pub fn delete_vertex_array(vertex_array_id: &mut VertexArrayId) {
+    unsafe { gl::DeleteVertexArrays(1, &vertex_array_id.raw_handle()) };
+    state_cache::forget_vertex_array(vertex_array_id.0);
+    vertex_array_id.0 = 0;
 }
@@ -0,0 +1,41 @@
+//! Command line options, looked up by name by whichever module they configure
+
+/// Whether `name` was passed, either as a flag or as an option with a value
+#[must_use]
+pub fn has_flag(name: &str) -> bool {
+    std::env::args().any(|arg| arg == name)
+}
+
+/// Argument following the first occurrence of `name`, `None` if either is missing
+#[must_use]
+pub fn value(name: &str) -> Option<String> {
+    value_in(std::env::args(), name)
+}
+
+fn value_in<I: IntoIterator<Item = String>>(args: I, name: &str) -> Option<String> {
+    let mut args = args.into_iter().skip_while(|arg| arg != name);
+    args.next()?;
+    args.next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::value_in;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn returns_argument_after_name() {
+        let args = args(&["app", "--capture", "png", "--capture-fps", "30"]);
+        assert_eq!(value_in(args.clone(), "--capture").as_deref(), Some("png"));
+        assert_eq!(value_in(args, "--capture-fps").as_deref(), Some("30"));
+    }
+
+    #[test]
+    fn returns_none_without_name_or_value() {
+        assert_eq!(value_in(args(&["app", "--font-size", "14"]), "--font"), None);
+        assert_eq!(value_in(args(&["app", "--font"]), "--font"), None);
+    }
+}
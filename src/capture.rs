@@ -0,0 +1,200 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::args;
+use crate::readback::{self, Image};
+
+const DEFAULT_FRAME_RATE: u32 = 60;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to save frame: {0}")]
+    Readback(#[from] readback::Error),
+
+    #[error("Frame size changed from {expected:?} to {actual:?}, which Y4M streams don't support")]
+    SizeChanged { expected: (usize, usize), actual: (usize, usize) },
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CaptureFormat {
+    /// Numbered PNG files within a directory
+    Png,
+    /// A single uncompressed YUV 4:4:4 stream, which e.g. ffmpeg converts to any video format
+    Y4m,
+}
+
+impl FromStr for CaptureFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> std::result::Result<Self, Self::Err> {
+        match format {
+            "png" => Ok(Self::Png),
+            "y4m" => Ok(Self::Y4m),
+            _ => Err(format!("Unknown capture format {format}, expected png or y4m")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CaptureSettings {
+    pub format: CaptureFormat,
+    /// Directory for PNG frames or file for Y4M streams, defaulting to `capture` and `capture.y4m`
+    pub path: Option<PathBuf>,
+    /// Rate of the simulated clock, each captured frame advances it by exactly one step
+    pub frame_rate: u32,
+}
+
+impl CaptureSettings {
+    /// Reads `--capture <png|y4m>`, `--capture-path <path>` and `--capture-fps <fps>`
+    ///
+    /// Returns whether `--capture` was given, in which case capturing should start with the first frame.
+    #[must_use]
+    pub fn from_args() -> (Self, bool) {
+        let format = args::value("--capture");
+        let settings = Self {
+            format: format.as_deref()
+                .and_then(|format| format.parse().map_err(|error| eprintln!("{error}")).ok())
+                .unwrap_or(CaptureFormat::Png),
+            path: args::value("--capture-path").map(PathBuf::from),
+            frame_rate: args::value("--capture-fps")
+                .and_then(|frame_rate| frame_rate.parse().ok())
+                .filter(|frame_rate| *frame_rate > 0)
+                .unwrap_or(DEFAULT_FRAME_RATE),
+        };
+
+        (settings, format.is_some())
+    }
+
+    #[must_use]
+    pub fn output_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| match self.format {
+            CaptureFormat::Png => PathBuf::from("capture"),
+            CaptureFormat::Y4m => PathBuf::from("capture.y4m"),
+        })
+    }
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self { format: CaptureFormat::Png, path: None, frame_rate: DEFAULT_FRAME_RATE }
+    }
+}
+
+#[derive(Debug)]
+enum Output {
+    Png { directory: PathBuf },
+    Y4m { writer: BufWriter<File>, size: Option<(usize, usize)> },
+}
+
+/// Writes every rendered frame while the application runs on a fixed simulated timestep.
+///
+/// Frames are read back synchronously, so none is ever skipped and the output doesn't depend on wall-clock time
+/// or how fast the machine renders.
+#[derive(Debug)]
+pub struct FrameCapture {
+    output: Output,
+    frame_rate: u32,
+    frames_written: u64,
+}
+
+impl FrameCapture {
+    /// # Errors
+    /// - Failed to create the output directory or file
+    pub fn start(settings: &CaptureSettings) -> Result<Self> {
+        let path = settings.output_path();
+        let output = match settings.format {
+            CaptureFormat::Png => {
+                fs::create_dir_all(&path)?;
+                Output::Png { directory: path }
+            }
+            CaptureFormat::Y4m => {
+                if let Some(directory) = path.parent() {
+                    fs::create_dir_all(directory)?;
+                }
+                Output::Y4m { writer: BufWriter::new(File::create(&path)?), size: None }
+            }
+        };
+
+        Ok(Self { output, frame_rate: settings.frame_rate, frames_written: 0 })
+    }
+
+    /// Time step to advance the simulation by for each frame
+    #[must_use]
+    pub fn delta_time(&self) -> f32 {
+        1f32 / self.frame_rate as f32
+    }
+
+    #[must_use]
+    pub fn frames_written(&self) -> u64 {
+        self.frames_written
+    }
+
+    /// Reads back `size` pixels of the bound framebuffer as the next frame, which must be called after rendering
+    ///
+    /// # Errors
+    /// - Failed to write the frame
+    /// - The size differs from the first frame of a Y4M stream
+    pub fn write_frame(&mut self, size: (usize, usize)) -> Result<()> {
+        let image = Image::read_framebuffer(size);
+        match &mut self.output {
+            Output::Png { directory } => {
+                image.save_png(&directory.join(format!("frame_{:06}.png", self.frames_written)))?;
+            }
+            Output::Y4m { writer, size: stream_size } => {
+                match *stream_size {
+                    None => {
+                        writeln!(writer, "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444", size.0, size.1, self.frame_rate)?;
+                        *stream_size = Some(size);
+                    }
+                    Some(expected) if expected != size => {
+                        return Err(Error::SizeChanged { expected, actual: size });
+                    }
+                    Some(_) => {}
+                }
+                writer.write_all(b"FRAME\n")?;
+                writer.write_all(&to_yuv444_planes(&image))?;
+            }
+        }
+        self.frames_written += 1;
+
+        Ok(())
+    }
+
+    /// Flushes the output and returns the number of written frames
+    ///
+    /// # Errors
+    /// - Failed to flush the Y4M stream
+    pub fn finish(mut self) -> Result<u64> {
+        if let Output::Y4m { writer, .. } = &mut self.output {
+            writer.flush()?;
+        }
+
+        Ok(self.frames_written)
+    }
+}
+
+/// Converts to planar, limited range BT.601 YUV, dropping alpha
+fn to_yuv444_planes(image: &Image) -> Vec<u8> {
+    let pixel_count = image.width * image.height;
+    let mut planes = vec![0u8; pixel_count * 3];
+    let (y_plane, chroma_planes) = planes.split_at_mut(pixel_count);
+    let (u_plane, v_plane) = chroma_planes.split_at_mut(pixel_count);
+
+    for (index, pixel) in image.pixels.chunks_exact(4).enumerate() {
+        let (r, g, b) = (i32::from(pixel[0]), i32::from(pixel[1]), i32::from(pixel[2]));
+        y_plane[index] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+        u_plane[index] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+        v_plane[index] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+    }
+
+    planes
+}
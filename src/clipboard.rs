@@ -0,0 +1,65 @@
+use std::fmt;
+
+use imgui::ClipboardBackend;
+use sdl2::clipboard::ClipboardUtil;
+
+/// Clipboard for Dear ImGui's text widgets.
+///
+/// With SDL's clipboard, which is shared with other applications, text is copied there. Without it, e.g. when
+/// rendering headlessly, and in the browser, copied text is kept in memory instead.
+/// Browsers additionally receive copied text through the asynchronous clipboard API, which can't be read back
+/// synchronously, so pasting only works within the application there.
+pub struct Clipboard {
+    sdl: Option<ClipboardUtil>,
+    text: Option<String>,
+}
+
+impl Clipboard {
+    #[must_use]
+    pub fn new(sdl: Option<ClipboardUtil>) -> Self {
+        Self { sdl, text: None }
+    }
+}
+
+impl fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Clipboard")
+            .field("sdl", &self.sdl.is_some())
+            .field("text", &self.text)
+            .finish()
+    }
+}
+
+impl ClipboardBackend for Clipboard {
+    fn get(&mut self) -> Option<String> {
+        match &self.sdl {
+            Some(sdl) => sdl.clipboard_text().ok(),
+            None => self.text.clone(),
+        }
+    }
+
+    fn set(&mut self, text: &str) {
+        if let Some(sdl) = &self.sdl {
+            // Dear ImGui never copies text containing NUL, which C strings can't represent anyway
+            if text.contains('\0') {
+                return;
+            }
+            if let Err(error) = sdl.set_clipboard_text(text) {
+                eprintln!("Failed to set clipboard text: {error}");
+            }
+            return;
+        }
+
+        #[cfg(target_os = "emscripten")]
+        write_browser_clipboard(text);
+        self.text = Some(text.to_owned());
+    }
+}
+
+#[cfg(target_os = "emscripten")]
+fn write_browser_clipboard(text: &str) {
+    let script = format!(
+        "if (navigator.clipboard) navigator.clipboard.writeText({}).catch(function () {{}});",
+        crate::emscripten::to_js_string_literal(text));
+    crate::emscripten::emscripten::run_script(&script);
+}
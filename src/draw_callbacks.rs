@@ -0,0 +1,78 @@
+use std::ffi::c_void;
+use std::fmt;
+
+use imgui::sys;
+
+/// Area a draw callback renders to, in framebuffer pixels with the origin at the bottom left like `gl::viewport`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DrawCallbackArea {
+    pub position: (usize, usize),
+    pub size: (usize, usize),
+}
+
+/// Handle of a callback registered with [`DrawCallbacks::register`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DrawCallbackId(usize);
+
+type DrawCallback = Box<dyn FnMut(DrawCallbackArea)>;
+
+/// Custom GL rendering which windows insert into Dear ImGui's draw order with [`add_draw_callback`].
+///
+/// Dear ImGui only stores a plain function pointer and a data pointer per callback, so callbacks are owned here
+/// and the draw list refers to them by id, which can't dangle like a pointer to a closure could.
+#[derive(Default)]
+pub struct DrawCallbacks {
+    /// Indexed by [`DrawCallbackId`]
+    callbacks: Vec<DrawCallback>,
+}
+
+impl DrawCallbacks {
+    pub fn register<F>(&mut self, callback: F) -> DrawCallbackId
+        where
+            F: FnMut(DrawCallbackArea) + 'static,
+    {
+        self.callbacks.push(Box::new(callback));
+        DrawCallbackId(self.callbacks.len() - 1)
+    }
+
+    /// Runs the callback `command` refers to, which must have been added by [`add_draw_callback`]
+    pub(crate) fn invoke(&mut self, command: &sys::ImDrawCmd, area: DrawCallbackArea) {
+        let id = command.UserCallbackData as usize;
+        // Ids registered with another `DrawCallbacks` may be out of range
+        if let Some(callback) = self.callbacks.get_mut(id) {
+            callback(area);
+        }
+    }
+}
+
+impl fmt::Debug for DrawCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DrawCallbacks")
+            .field("registered", &self.callbacks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Inserts the registered callback `id` at the current position of the current window's draw list.
+///
+/// The callback renders to the rectangle from `min` to `max` in screen coordinates, e.g. from
+/// `ui.cursor_screen_pos()` to that plus the size passed to `ui.dummy`. Viewport and scissor box are set to the
+/// part of it visible within the window, and Dear ImGui's render state is set up again after the callback returns.
+pub fn add_draw_callback(_ui: &imgui::Ui, id: DrawCallbackId, min: [f32; 2], max: [f32; 2]) {
+    unsafe {
+        let draw_list = sys::igGetWindowDrawList();
+        sys::ImDrawList_PushClipRect(draw_list, min.into(), max.into(), true);
+        // The id is smuggled through the data pointer, so nothing is allocated that could leak if the frame is dropped
+        sys::ImDrawList_AddCallback(draw_list, Some(run_draw_callback), id.0 as *mut c_void);
+        sys::ImDrawList_PopClipRect(draw_list);
+    }
+}
+
+/// Whether `command` was added by [`add_draw_callback`] rather than through Dear ImGui's raw callbacks
+pub(crate) fn is_draw_callback(command: &sys::ImDrawCmd) -> bool {
+    let marker: unsafe extern "C" fn(*const sys::ImDrawList, *const sys::ImDrawCmd) = run_draw_callback;
+    command.UserCallback.map(|callback| callback as usize) == Some(marker as usize)
+}
+
+/// Marks callbacks added by [`add_draw_callback`], which the renderer runs through [`DrawCallbacks`] instead
+unsafe extern "C" fn run_draw_callback(_draw_list: *const sys::ImDrawList, _command: *const sys::ImDrawCmd) {}
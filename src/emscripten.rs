@@ -1,7 +1,10 @@
+use std::fmt::Write;
+
 #[cfg(target_os = "emscripten")]
 pub mod emscripten {
     use std::cell::RefCell;
-    use std::os::raw::{c_float, c_int, c_void, c_uint};
+    use std::ffi::{CStr, CString};
+    use std::os::raw::{c_char, c_double, c_int, c_void, c_uint};
     use std::ptr::null_mut;
 
     #[allow(non_camel_case_types)]
@@ -10,16 +13,40 @@ pub mod emscripten {
     extern {
         pub fn emscripten_set_main_loop(func: em_callback_func, fps: c_int, simulate_infinite_loop: c_int);
         pub fn emscripten_cancel_main_loop();
-        pub fn emscripten_get_now() -> c_float;
+        pub fn emscripten_get_now() -> c_double;
         pub fn emscripten_sleep(ms: c_uint);
+        pub fn emscripten_run_script(script: *const c_char);
+        pub fn emscripten_run_script_string(script: *const c_char) -> *const c_char;
     }
 
     thread_local!(static MAIN_LOOP_CALLBACK: RefCell<*mut c_void> = RefCell::new(null_mut()));
 
+    /// Milliseconds since the page loaded, with sub-millisecond precision where the browser allows it
+    pub fn now() -> f64 {
+        unsafe { emscripten_get_now() }
+    }
+
     pub fn sleep(ms: u32) {
         unsafe { emscripten_sleep(ms as c_uint) };
     }
 
+    pub fn run_script(script: &str) {
+        let script = CString::new(script).expect("Script must not contain NUL");
+        unsafe { emscripten_run_script(script.as_ptr()) };
+    }
+
+    /// Evaluates `script` and returns its result as a string, `None` if it's `null` or `undefined`
+    pub fn run_script_string(script: &str) -> Option<String> {
+        let script = CString::new(script).expect("Script must not contain NUL");
+        let result = unsafe { emscripten_run_script_string(script.as_ptr()) };
+        if result.is_null() {
+            return None;
+        }
+
+        // The result lives in a buffer which the next call overwrites
+        Some(unsafe { CStr::from_ptr(result) }.to_string_lossy().into_owned())
+    }
+
     pub fn set_main_loop_callback<F>(callback: F) where F: FnMut() {
         MAIN_LOOP_CALLBACK.with(|log| {
             *log.borrow_mut() = &callback as *const _ as *mut c_void;
@@ -35,3 +62,58 @@ pub mod emscripten {
         }
     }
 }
+
+/// Quotes and escapes `text`, so it can't terminate the literal or the surrounding script
+pub fn to_js_string_literal(text: &str) -> String {
+    let mut literal = String::with_capacity(text.len() + 2);
+    literal.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            character if character.is_control() || character == '\u{2028}' || character == '\u{2029}' => {
+                let _ = write!(literal, "\\u{:04x}", u32::from(character));
+            }
+            character => literal.push(character),
+        }
+    }
+    literal.push('"');
+
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_js_string_literal;
+
+    #[test]
+    fn quotes_plain_text() {
+        assert_eq!(to_js_string_literal("opengl-web/settings.ini"), r#""opengl-web/settings.ini""#);
+        assert_eq!(to_js_string_literal(""), r#""""#);
+    }
+
+    #[test]
+    fn escapes_quotes_and_backslashes() {
+        assert_eq!(to_js_string_literal(r#"say "hi" \ bye"#), r#""say \"hi\" \\ bye""#);
+        assert_eq!(to_js_string_literal("'single'"), r#""'single'""#);
+    }
+
+    #[test]
+    fn escapes_line_terminators() {
+        assert_eq!(to_js_string_literal("a\nb\r\tc"), r#""a\nb\r\tc""#);
+        assert_eq!(to_js_string_literal("a\u{2028}b\u{2029}c"), r#""a\u2028b\u2029c""#);
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        assert_eq!(to_js_string_literal("\0\u{1b}[0m\u{7f}"), r#""\u0000\u001b[0m\u007f""#);
+    }
+
+    #[test]
+    fn keeps_other_unicode() {
+        assert_eq!(to_js_string_literal("γ = 2.2 ✓"), r#""γ = 2.2 ✓""#);
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(not(target_os = "emscripten"))]
+use std::time::Instant;
+
+mod gl {
+    pub use gl::program::*;
+}
+
+/// Weight of the latest frame in the smoothed frame rate
+const SMOOTHING: f32 = 0.1;
+
+/// Measures frame times and tracks the time the application has simulated so far
+#[derive(Debug)]
+pub struct FrameTimer {
+    last_tick: Timestamp,
+    delta_time: f32,
+    smoothed_delta_time: Option<f32>,
+    total_time: f64,
+    frame_count: u64,
+}
+
+impl FrameTimer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            last_tick: Timestamp::now(),
+            delta_time: 0f32,
+            smoothed_delta_time: None,
+            total_time: 0f64,
+            frame_count: 0,
+        }
+    }
+
+    /// Starts the next frame, advancing by the wall-clock time since the previous one
+    pub fn tick(&mut self) {
+        let now = Timestamp::now();
+        let elapsed = now.seconds_since(&self.last_tick);
+        self.last_tick = now;
+        self.advance(elapsed);
+    }
+
+    /// Starts the next frame, advancing by exactly `delta_time` regardless of how much time passed
+    pub fn tick_fixed(&mut self, delta_time: f32) {
+        self.last_tick = Timestamp::now();
+        self.advance(delta_time);
+    }
+
+    fn advance(&mut self, delta_time: f32) {
+        // Dear ImGui requires time to advance on every frame
+        self.delta_time = delta_time.max(f32::MIN_POSITIVE);
+        self.smoothed_delta_time = Some(self.smoothed_delta_time.map_or(self.delta_time, |smoothed| {
+            smoothed + (self.delta_time - smoothed) * SMOOTHING
+        }));
+        self.total_time += f64::from(self.delta_time);
+        self.frame_count += 1;
+    }
+
+    /// Seconds the current frame advanced by
+    #[must_use]
+    pub fn delta_time(&self) -> f32 {
+        self.delta_time
+    }
+
+    /// Frames per second, averaged over recent frames
+    #[must_use]
+    pub fn fps(&self) -> f32 {
+        self.smoothed_delta_time.map_or(0f32, |smoothed| 1f32 / smoothed)
+    }
+
+    /// Seconds advanced over all frames
+    #[must_use]
+    pub fn total_time(&self) -> f64 {
+        self.total_time
+    }
+
+    #[must_use]
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Uploads `time`, `delta_time` and `frame` to the bound `program`, skipping uniforms it doesn't declare
+    pub fn set_uniforms(&self, program: gl::ProgramId) {
+        gl::uniform(gl::uniform_location(program, "time"), self.total_time as f32);
+        gl::uniform(gl::uniform_location(program, "delta_time"), self.delta_time);
+        // Wraps after 2^31 frames, which takes more than a year at 60 fps
+        gl::uniform(gl::uniform_location(program, "frame"), self.frame_count as i32);
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+struct Timestamp {
+    #[cfg(not(target_os = "emscripten"))]
+    instant: Instant,
+    /// Milliseconds since the page loaded
+    #[cfg(target_os = "emscripten")]
+    milliseconds: f64,
+}
+
+impl Timestamp {
+    fn now() -> Self {
+        #[cfg(not(target_os = "emscripten"))]
+        return Self { instant: Instant::now() };
+        #[cfg(target_os = "emscripten")]
+        return Self { milliseconds: crate::emscripten::emscripten::now() };
+    }
+
+    fn seconds_since(&self, earlier: &Self) -> f32 {
+        #[cfg(not(target_os = "emscripten"))]
+        return self.instant.duration_since(earlier.instant).as_secs_f32();
+        #[cfg(target_os = "emscripten")]
+        return ((self.milliseconds - earlier.milliseconds) / 1000f64) as f32;
+    }
+}
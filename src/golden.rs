@@ -0,0 +1,201 @@
+//! Golden-image regression tests.
+//!
+//! Scenes are rendered into a headless context, read back and compared against the reference PNGs in
+//! `tests/golden`. Failing comparisons write the actual image and a diff to `target/golden`.
+//! Running the tests with `BLESS=1` replaces the references with the rendered images instead.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use crate::headless::HeadlessContext;
+use crate::readback::Image;
+
+const REFERENCE_DIR: &str = "tests/golden";
+const OUTPUT_DIR: &str = "target/golden";
+
+/// Only one GL context and Dear ImGui context may exist at a time, while tests run on parallel threads
+static RENDER_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Copy, Clone)]
+pub struct Tolerance {
+    /// Largest difference of any channel for which a pixel still matches the reference
+    pub channel: u8,
+    /// Fraction of mismatching pixels above which the comparison fails
+    pub max_failing_ratio: f32,
+}
+
+impl Tolerance {
+    pub const DEFAULT: Self = Self { channel: 2, max_failing_ratio: 0.001 };
+}
+
+fn load_png(path: &Path) -> Image {
+    let decoder = png::Decoder::new(BufReader::new(File::open(path).expect("Failed to open PNG")));
+    let mut reader = decoder.read_info().expect("Failed to read PNG header");
+    let mut pixels = vec![0u8; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).expect("Failed to decode PNG");
+    assert_eq!(
+        (info.color_type, info.bit_depth),
+        (png::ColorType::Rgba, png::BitDepth::Eight),
+        "Reference {} must be an RGBA8 PNG", path.display());
+    pixels.truncate(info.buffer_size());
+
+    Image { width: info.width as usize, height: info.height as usize, pixels }
+}
+
+fn save_png(image: &Image, path: &Path) {
+    image.save_png(path)
+        .unwrap_or_else(|error| panic!("Failed to write {}: {error}", path.display()));
+}
+
+/// Runs `render_frame` in a fresh headless context of `size` and reads back the rendered image.
+///
+/// Making the context current resets the tracked GL state, so nothing carries over from earlier renders on the thread.
+pub fn render<F: FnOnce()>(size: (usize, usize), render_frame: F) -> Image {
+    let _lock = RENDER_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let context = HeadlessContext::new(size).expect("Failed to create headless context");
+
+    render_frame();
+
+    context.bind();
+    Image::read_framebuffer(size)
+}
+
+/// # Panics
+/// - The reference is missing, differs in size or more pixels than tolerated mismatch
+pub fn assert_matches_golden(name: &str, actual: &Image, tolerance: Tolerance) {
+    let reference_path = manifest_path(REFERENCE_DIR).join(format!("{name}.png"));
+    if std::env::var("BLESS").is_ok_and(|bless| bless == "1") {
+        save_png(actual, &reference_path);
+        return;
+    }
+
+    assert!(
+        reference_path.exists(),
+        "Missing golden image {}, run the tests with BLESS=1 to create it", reference_path.display());
+    let reference = load_png(&reference_path);
+
+    let output_dir = manifest_path(OUTPUT_DIR);
+    let actual_path = output_dir.join(format!("{name}.actual.png"));
+    if (reference.width, reference.height) != (actual.width, actual.height) {
+        save_png(actual, &actual_path);
+        panic!(
+            "Golden image {name} is {}x{}, but {}x{} was rendered, see {}",
+            reference.width, reference.height, actual.width, actual.height, actual_path.display());
+    }
+
+    let (failing_pixels, diff) = compare(&reference, actual, tolerance.channel);
+    let pixel_count = actual.width * actual.height;
+    let failing_ratio = failing_pixels as f32 / pixel_count as f32;
+    if failing_ratio > tolerance.max_failing_ratio {
+        let diff_path = output_dir.join(format!("{name}.diff.png"));
+        save_png(actual, &actual_path);
+        save_png(&diff, &diff_path);
+        panic!(
+            "Golden image {name} mismatch: {failing_pixels} of {pixel_count} pixels ({:.3}%) differ by more than {}, \
+             at most {:.3}% are tolerated. See {} and {}",
+            failing_ratio * 100f32,
+            tolerance.channel,
+            tolerance.max_failing_ratio * 100f32,
+            actual_path.display(),
+            diff_path.display());
+    }
+}
+
+/// Counts the mismatching pixels and marks them red in a diff image, over a darkened reference
+fn compare(reference: &Image, actual: &Image, channel_tolerance: u8) -> (usize, Image) {
+    let mut failing_pixels = 0;
+    let mut diff_pixels = Vec::with_capacity(reference.pixels.len());
+    for (reference_pixel, actual_pixel) in reference.pixels.chunks_exact(4).zip(actual.pixels.chunks_exact(4)) {
+        let matches = reference_pixel.iter()
+            .zip(actual_pixel)
+            .all(|(reference_channel, actual_channel)| reference_channel.abs_diff(*actual_channel) <= channel_tolerance);
+        if matches {
+            let luminance = reference_pixel[..3].iter().map(|channel| u16::from(*channel)).sum::<u16>() / 3;
+            let dimmed = (luminance / 4) as u8;
+            diff_pixels.extend_from_slice(&[dimmed, dimmed, dimmed, 0xFF]);
+        } else {
+            failing_pixels += 1;
+            diff_pixels.extend_from_slice(&[0xFF, 0, 0, 0xFF]);
+        }
+    }
+
+    (failing_pixels, Image { width: reference.width, height: reference.height, pixels: diff_pixels })
+}
+
+fn manifest_path(relative_path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative_path)
+}
+
+mod tests {
+    use imgui::Condition;
+
+    use crate::frame_timer::FrameTimer;
+    use crate::imgui_wrapper::Imgui;
+    use crate::resources::Resources;
+    use crate::scene::Scene;
+
+    use super::{assert_matches_golden, manifest_path, render, Tolerance};
+
+    mod gl {
+        pub use gl::rendering::*;
+    }
+
+    fn draw_gamma_triangle() {
+        let resources = Resources::from_path(&manifest_path("assets"));
+        let scene = Scene::new(&resources).expect("Failed to create scene");
+        let mut frame_timer = FrameTimer::new();
+        frame_timer.tick_fixed(1f32 / 60f32);
+        scene.draw(2.2f32, &frame_timer);
+    }
+
+    #[test]
+    fn gamma_triangle() {
+        let image = render((128, 128), draw_gamma_triangle);
+
+        assert_matches_golden("gamma_triangle", &image, Tolerance::DEFAULT);
+    }
+
+    #[test]
+    fn imgui_frame() {
+        let size = (320, 240);
+        let image = render(size, || {
+            let mut imgui = Imgui::init(None);
+            let mut value = 0.5f32;
+            let mut checked = true;
+
+            // Auto-sized content is only laid out correctly from the second frame on
+            for _ in 0..2 {
+                imgui.prepare([size.0 as f32, size.1 as f32], [1f32, 1f32], 1f32 / 60f32);
+                gl::clear_color(0xFF202020);
+                gl::clear(gl::COLOR);
+                imgui.render(|ui| {
+                    ui.window("Golden")
+                        .position([10f32, 10f32], Condition::Always)
+                        .size([300f32, 220f32], Condition::Always)
+                        .save_settings(false)
+                        .build(|| {
+                            ui.text("The quick brown fox");
+                            ui.button("Button");
+                            ui.slider("Slider", 0f32, 1f32, &mut value);
+                            ui.checkbox("Checkbox", &mut checked);
+                            ui.separator();
+                            ui.text_colored([1f32, 0.5f32, 0f32, 1f32], "Colored text");
+                        });
+                });
+            }
+        });
+
+        assert_matches_golden("imgui_frame", &image, Tolerance::DEFAULT);
+    }
+
+    /// The pipeline state tracked for the first context must not skip state changes in the second one
+    #[test]
+    fn gamma_triangle_in_second_context() {
+        render((128, 128), draw_gamma_triangle);
+        let image = render((128, 128), draw_gamma_triangle);
+
+        assert_matches_golden("gamma_triangle", &image, Tolerance::DEFAULT);
+    }
+}
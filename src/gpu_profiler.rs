@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+
+use imgui::Condition;
+
+mod gl {
+    pub use gl::query::*;
+}
+
+/// Number of frames whose queries may be in flight before their results are discarded
+const FRAMES_IN_FLIGHT: usize = 4;
+const HISTORY_LENGTH: usize = 120;
+
+#[derive(Debug)]
+struct PendingScope {
+    name: String,
+    depth: usize,
+    begin_query: gl::QueryId,
+    end_query: gl::QueryId,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScopeTiming {
+    pub name: String,
+    pub depth: usize,
+    pub milliseconds: f32,
+}
+
+/// Measures GPU time of nested, named scopes through timestamp queries.
+///
+/// Results are only read back once available, usually a few frames later, so profiling never stalls the pipeline.
+#[derive(Debug)]
+pub struct GpuProfiler {
+    supported: bool,
+    free_queries: Vec<gl::QueryId>,
+    open_scopes: Vec<usize>,
+    current_frame: Vec<PendingScope>,
+    pending_frames: VecDeque<Vec<PendingScope>>,
+    timings: Vec<ScopeTiming>,
+    history: Vec<(String, Vec<f32>)>,
+}
+
+impl GpuProfiler {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            supported: gl::timer_queries_supported(),
+            free_queries: Vec::new(),
+            open_scopes: Vec::new(),
+            current_frame: Vec::new(),
+            pending_frames: VecDeque::with_capacity(FRAMES_IN_FLIGHT),
+            timings: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Collects the results of all previous frames which finished on the GPU
+    pub fn begin_frame(&mut self) {
+        if !self.supported {
+            return;
+        }
+
+        if gl::gpu_disjoint() {
+            while let Some(frame) = self.pending_frames.pop_front() {
+                self.release(frame);
+            }
+            return;
+        }
+
+        while let Some(frame) = self.pending_frames.front() {
+            let available = frame.iter()
+                .all(|scope| gl::query_result_available(scope.end_query));
+            if !available {
+                break;
+            }
+
+            let frame = self.pending_frames.pop_front().unwrap();
+            // Frames without scopes keep showing the previous results
+            if !frame.is_empty() {
+                self.timings = frame.iter()
+                    .map(|scope| {
+                        let begin = gl::query_result_u64(scope.begin_query);
+                        let end = gl::query_result_u64(scope.end_query);
+                        ScopeTiming {
+                            name: scope.name.clone(),
+                            depth: scope.depth,
+                            milliseconds: (end.saturating_sub(begin) as f64 / 1_000_000f64) as f32,
+                        }
+                    })
+                    .collect();
+                self.record_history();
+            }
+            self.release(frame);
+        }
+    }
+
+    /// # Panics
+    /// - A scope is still open
+    pub fn end_frame(&mut self) {
+        if !self.supported {
+            return;
+        }
+
+        assert!(self.open_scopes.is_empty(), "GPU profiler scope left open at end of frame");
+        if self.pending_frames.len() == FRAMES_IN_FLIGHT {
+            let frame = self.pending_frames.pop_front().unwrap();
+            self.release(frame);
+        }
+        let frame = std::mem::take(&mut self.current_frame);
+        self.pending_frames.push_back(frame);
+    }
+
+    pub fn begin_scope(&mut self, name: &str) {
+        if !self.supported {
+            return;
+        }
+
+        let begin_query = self.acquire_query();
+        let end_query = self.acquire_query();
+        gl::query_timestamp(begin_query);
+        self.open_scopes.push(self.current_frame.len());
+        self.current_frame.push(PendingScope {
+            name: String::from(name),
+            depth: self.open_scopes.len() - 1,
+            begin_query,
+            end_query,
+        });
+    }
+
+    /// # Panics
+    /// - No scope is open
+    pub fn end_scope(&mut self) {
+        if !self.supported {
+            return;
+        }
+
+        let index = self.open_scopes.pop()
+            .expect("GPU profiler scope ended without being begun");
+        gl::query_timestamp(self.current_frame[index].end_query);
+    }
+
+    pub fn scope<R, F>(&mut self, name: &str, f: F) -> R
+        where
+            F: FnOnce(&mut Self) -> R,
+    {
+        self.begin_scope(name);
+        let result = f(self);
+        self.end_scope();
+        result
+    }
+
+    pub fn draw_ui(&self, ui: &imgui::Ui) {
+        ui.window("GPU Profiler")
+            .position([300f32, 60f32], Condition::FirstUseEver)
+            .always_auto_resize(true)
+            .build(|| {
+                if !self.supported {
+                    ui.text("Timer queries are not supported by this context");
+                    return;
+                }
+
+                ui.columns(3, "Timings", true);
+                ui.text("Pass");
+                ui.next_column();
+                ui.text("GPU (ms)");
+                ui.next_column();
+                ui.text("History");
+                ui.next_column();
+                ui.separator();
+
+                for timing in &self.timings {
+                    ui.text(format!("{}{}", "  ".repeat(timing.depth), timing.name));
+                    ui.next_column();
+                    ui.text(format!("{:.3}", timing.milliseconds));
+                    ui.next_column();
+                    if let Some((name, history)) = self.history.iter().find(|(name, _)| *name == timing.name) {
+                        ui.plot_lines(format!("##{name}"), history)
+                            .scale_min(0f32)
+                            .graph_size([120f32, 20f32])
+                            .build();
+                    }
+                    ui.next_column();
+                }
+                ui.columns(1, "Timings", false);
+            });
+    }
+
+    fn record_history(&mut self) {
+        for timing in &self.timings {
+            let index = match self.history.iter().position(|(name, _)| *name == timing.name) {
+                Some(index) => index,
+                None => {
+                    self.history.push((timing.name.clone(), Vec::with_capacity(HISTORY_LENGTH)));
+                    self.history.len() - 1
+                }
+            };
+            let history = &mut self.history[index].1;
+            if history.len() == HISTORY_LENGTH {
+                history.remove(0);
+            }
+            history.push(timing.milliseconds);
+        }
+    }
+
+    fn acquire_query(&mut self) -> gl::QueryId {
+        self.free_queries.pop()
+            .unwrap_or_else(gl::gen_query)
+    }
+
+    fn release(&mut self, frame: Vec<PendingScope>) {
+        for scope in frame {
+            self.free_queries.push(scope.begin_query);
+            self.free_queries.push(scope.end_query);
+        }
+    }
+}
+
+impl Drop for GpuProfiler {
+    fn drop(&mut self) {
+        let frames = std::mem::take(&mut self.pending_frames);
+        for frame in frames {
+            self.release(frame);
+        }
+        let current_frame = std::mem::take(&mut self.current_frame);
+        self.release(current_frame);
+        for query in &mut self.free_queries {
+            gl::delete_query(query);
+        }
+    }
+}
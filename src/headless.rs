@@ -0,0 +1,196 @@
+use std::ffi::c_void;
+
+use khronos_egl as egl;
+use thiserror::Error;
+
+mod gl {
+    pub use gl::context::*;
+    pub use gl::framebuffer::*;
+    pub use gl::state::*;
+}
+
+/// From `EGL_MESA_platform_surfaceless`, which isn't part of the EGL headers
+const PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to load libEGL: {0}")]
+    Load(String),
+
+    #[error("EGL error: {0}")]
+    Egl(#[from] egl::Error),
+
+    #[error("No EGL display available")]
+    NoDisplay,
+
+    #[error("No EGL config supports the requested API")]
+    NoConfig,
+
+    #[error("Offscreen framebuffer is incomplete: {0:?}")]
+    IncompleteFramebuffer(gl::FramebufferStatus),
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Offscreen context created through EGL without any window, e.g. on machines without a display.
+///
+/// Prefers Mesa's surfaceless platform and falls back to the default display with a pbuffer surface.
+/// The context matches the generated bindings, GLES 3.0 for `gl_es` and GL 4.5 core otherwise.
+/// Rendering goes to an owned framebuffer of the requested size, which is bound on creation.
+#[derive(Debug)]
+pub struct HeadlessContext {
+    egl: egl::DynamicInstance<egl::EGL1_4>,
+    display: egl::Display,
+    context: egl::Context,
+    surface: Option<egl::Surface>,
+    /// Function pointers of the EGL driver, which may differ from the one windows are created with
+    gl: gl::Context,
+    size: (usize, usize),
+    framebuffer: gl::FramebufferId,
+    color_renderbuffer: gl::RenderbufferId,
+    depth_stencil_renderbuffer: gl::RenderbufferId,
+}
+
+impl HeadlessContext {
+    /// Creates the context, makes it and the GL bindings loaded from it current on the calling thread
+    ///
+    /// # Errors
+    /// - libEGL couldn't be loaded
+    /// - No display, config or context supporting the API of the bindings is available
+    /// - The offscreen framebuffer is incomplete
+    pub fn new(size: (usize, usize)) -> Result<Self> {
+        let egl = unsafe { egl::DynamicInstance::<egl::EGL1_4>::load_required() }
+            .map_err(|error| Error::Load(error.to_string()))?;
+        let display = get_display(&egl)?;
+        egl.initialize(display)?;
+
+        let display_extensions = egl.query_string(Some(display), egl::EXTENSIONS)?.to_string_lossy();
+        let surfaceless = display_extensions.split(' ').any(|extension| extension == "EGL_KHR_surfaceless_context");
+
+        let (api, renderable_type, context_attributes) = if cfg!(gl_es) {
+            (egl::OPENGL_ES_API, egl::OPENGL_ES3_BIT, vec![
+                egl::CONTEXT_MAJOR_VERSION, 3,
+                egl::CONTEXT_MINOR_VERSION, 0,
+                egl::NONE,
+            ])
+        } else {
+            (egl::OPENGL_API, egl::OPENGL_BIT, vec![
+                egl::CONTEXT_MAJOR_VERSION, 4,
+                egl::CONTEXT_MINOR_VERSION, 5,
+                egl::CONTEXT_OPENGL_PROFILE_MASK, egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                egl::NONE,
+            ])
+        };
+        egl.bind_api(api)?;
+
+        let surface_type = if surfaceless { 0 } else { egl::PBUFFER_BIT };
+        let config = egl.choose_first_config(display, &[
+            egl::RENDERABLE_TYPE, renderable_type,
+            egl::SURFACE_TYPE, surface_type,
+            egl::NONE,
+        ])?.ok_or(Error::NoConfig)?;
+        let context = egl.create_context(display, config, None, &context_attributes)?;
+
+        // Rendering always targets the framebuffer, the pbuffer only exists to make the context current
+        let surface = if surfaceless {
+            None
+        } else {
+            Some(egl.create_pbuffer_surface(display, config, &[egl::WIDTH, 1, egl::HEIGHT, 1, egl::NONE])?)
+        };
+        egl.make_current(display, surface, surface, Some(context))?;
+
+        let gl = gl::Context::load_with(|name| {
+            egl.get_proc_address(name)
+                .map_or(std::ptr::null(), |function| function as *const c_void)
+        });
+        // Also invalidates the state cache and pipeline state, which may still describe an earlier context
+        gl.make_current();
+
+        let mut headless_context = Self {
+            egl,
+            display,
+            context,
+            surface,
+            gl,
+            size,
+            framebuffer: gl::DEFAULT_FRAMEBUFFER,
+            color_renderbuffer: gl::NO_RENDERBUFFER,
+            depth_stencil_renderbuffer: gl::NO_RENDERBUFFER,
+        };
+        headless_context.create_framebuffer()?;
+
+        Ok(headless_context)
+    }
+
+    #[must_use]
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Binds the framebuffer and resets the viewport to cover it
+    pub fn bind(&self) {
+        gl::bind_framebuffer(gl::FRAMEBUFFER, self.framebuffer);
+        gl::viewport((0, 0), self.size);
+    }
+
+    fn create_framebuffer(&mut self) -> Result<()> {
+        self.color_renderbuffer = gl::gen_renderbuffer();
+        gl::bind_renderbuffer(self.color_renderbuffer);
+        gl::renderbuffer_storage(gl::RGBA8, self.size);
+
+        self.depth_stencil_renderbuffer = gl::gen_renderbuffer();
+        gl::bind_renderbuffer(self.depth_stencil_renderbuffer);
+        gl::renderbuffer_storage(gl::DEPTH24_STENCIL8, self.size);
+        gl::bind_renderbuffer(gl::NO_RENDERBUFFER);
+
+        self.framebuffer = gl::gen_framebuffer();
+        gl::bind_framebuffer(gl::FRAMEBUFFER, self.framebuffer);
+        gl::framebuffer_renderbuffer(gl::FRAMEBUFFER, gl::color_attachment(0), self.color_renderbuffer);
+        gl::framebuffer_renderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, self.depth_stencil_renderbuffer);
+
+        match gl::check_framebuffer_status(gl::FRAMEBUFFER) {
+            gl::FramebufferStatus::Complete => {
+                gl::viewport((0, 0), self.size);
+                Ok(())
+            }
+            status => Err(Error::IncompleteFramebuffer(status)),
+        }
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        gl::bind_framebuffer(gl::FRAMEBUFFER, gl::DEFAULT_FRAMEBUFFER);
+        gl::delete_framebuffer(&mut self.framebuffer);
+        gl::delete_renderbuffer(&mut self.color_renderbuffer);
+        gl::delete_renderbuffer(&mut self.depth_stencil_renderbuffer);
+
+        // Errors can't be handled meaningfully anymore while tearing down
+        let _ = self.egl.make_current(self.display, None, None, None);
+        if let Some(surface) = self.surface.take() {
+            let _ = self.egl.destroy_surface(self.display, surface);
+        }
+        let _ = self.egl.destroy_context(self.display, self.context);
+        let _ = self.egl.terminate(self.display);
+        if self.gl.is_current() {
+            gl::Context::clear_current();
+        }
+    }
+}
+
+/// Mesa's surfaceless platform works without any windowing system, the default display may need one
+fn get_display(egl: &egl::DynamicInstance<egl::EGL1_4>) -> Result<egl::Display> {
+    // Querying client extensions fails on implementations without EGL_EXT_client_extensions
+    let client_extensions = egl.query_string(None, egl::EXTENSIONS)
+        .map(|extensions| extensions.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let surfaceless_platform = client_extensions.split(' ').any(|extension| extension == "EGL_MESA_platform_surfaceless");
+
+    if surfaceless_platform {
+        if let Some(egl) = egl.upcast::<egl::EGL1_5>() {
+            return Ok(unsafe { egl.get_platform_display(PLATFORM_SURFACELESS_MESA, egl::DEFAULT_DISPLAY, &[egl::ATTRIB_NONE])? });
+        }
+    }
+
+    unsafe { egl.get_display(egl::DEFAULT_DISPLAY) }.ok_or(Error::NoDisplay)
+}
@@ -0,0 +1,211 @@
+use std::str::FromStr;
+
+use imgui::{FontAtlas, FontConfig, FontGlyphRanges, FontSource};
+use thiserror::Error;
+
+use crate::args;
+use crate::resources::{self, Resources};
+
+/// Size of Dear ImGui's built-in font, which is only legible at multiples of it
+const DEFAULT_FONT_SIZE: f32 = 13f32;
+
+/// Unicode's Private Use Area, where icon fonts like Font Awesome or Material Icons place their icons
+static ICON_GLYPH_RANGES: [u32; 3] = [0xE000, 0xF8FF, 0];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to load font {name}: {source}")]
+    Resource {
+        name: String,
+        source: resources::Error,
+    },
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GlyphRanges {
+    /// Basic Latin and Latin-1 Supplement
+    Latin,
+    /// Latin and Cyrillic
+    Cyrillic,
+    /// Latin, Japanese kana and the 2500 most common simplified Chinese ideograms
+    Chinese,
+    /// Latin, kana and the 2999 most common Japanese kanji
+    Japanese,
+    /// Latin and Hangul
+    Korean,
+    /// Private Use Area, for merging icon fonts
+    Icons,
+}
+
+impl GlyphRanges {
+    fn to_imgui(self) -> FontGlyphRanges {
+        match self {
+            Self::Latin => FontGlyphRanges::default(),
+            Self::Cyrillic => FontGlyphRanges::cyrillic(),
+            Self::Chinese => FontGlyphRanges::chinese_simplified_common(),
+            Self::Japanese => FontGlyphRanges::japanese(),
+            Self::Korean => FontGlyphRanges::korean(),
+            Self::Icons => FontGlyphRanges::from_slice(&ICON_GLYPH_RANGES),
+        }
+    }
+}
+
+impl FromStr for GlyphRanges {
+    type Err = String;
+
+    fn from_str(glyph_ranges: &str) -> std::result::Result<Self, Self::Err> {
+        match glyph_ranges {
+            "latin" => Ok(Self::Latin),
+            "cyrillic" => Ok(Self::Cyrillic),
+            "chinese" => Ok(Self::Chinese),
+            "japanese" => Ok(Self::Japanese),
+            "korean" => Ok(Self::Korean),
+            "icons" => Ok(Self::Icons),
+            _ => Err(format!(
+                "Unknown glyph ranges {glyph_ranges}, expected latin, cyrillic, chinese, japanese, korean or icons")),
+        }
+    }
+}
+
+/// Glyphs of one font file, several of which can be merged into one font
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFace {
+    /// TTF or OTF file within the resources, or Dear ImGui's built-in font if `None`
+    pub resource_name: Option<String>,
+    /// Size in window coordinates, which is multiplied by the framebuffer scale for rasterizing
+    pub size: f32,
+    pub glyph_ranges: GlyphRanges,
+}
+
+impl FontFace {
+    /// Reads `--font <resource>`, `--font-size <size>`, `--font-glyphs <ranges>` and `--icon-font <resource>`
+    ///
+    /// Returns the font face and the icon font to merge into it, `None` if neither font was given.
+    #[must_use]
+    pub fn from_args() -> Option<(Self, Option<Self>)> {
+        let resource_name = args::value("--font");
+        let icon_resource_name = args::value("--icon-font");
+        if resource_name.is_none() && icon_resource_name.is_none() {
+            return None;
+        }
+
+        let size = args::value("--font-size")
+            .and_then(|size| size.parse().ok())
+            .filter(|size: &f32| *size > 0f32)
+            .unwrap_or(DEFAULT_FONT_SIZE);
+        let face = Self {
+            resource_name,
+            size,
+            glyph_ranges: args::value("--font-glyphs")
+                .and_then(|glyph_ranges| glyph_ranges.parse().map_err(|error| eprintln!("{error}")).ok())
+                .unwrap_or(GlyphRanges::Latin),
+        };
+        let icons = icon_resource_name.map(|resource_name| Self {
+            resource_name: Some(resource_name),
+            size,
+            glyph_ranges: GlyphRanges::Icons,
+        });
+
+        Some((face, icons))
+    }
+}
+
+impl Default for FontFace {
+    fn default() -> Self {
+        Self { resource_name: None, size: DEFAULT_FONT_SIZE, glyph_ranges: GlyphRanges::Latin }
+    }
+}
+
+#[derive(Debug)]
+struct LoadedFace {
+    face: FontFace,
+    data: Option<Vec<u8>>,
+}
+
+/// Fonts of Dear ImGui's font atlas, whose files are kept loaded so the atlas can be rebuilt at another scale
+#[derive(Debug)]
+pub struct Fonts {
+    /// The first face of each font is its base, the others are merged into it
+    fonts: Vec<Vec<LoadedFace>>,
+    /// Framebuffer scale the atlas was last built for, `None` if fonts were added since
+    built_scale: Option<f32>,
+}
+
+impl Fonts {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { fonts: Vec::new(), built_scale: None }
+    }
+
+    /// Loads a font whose glyphs missing from `face` are taken from `merged`, e.g. icons
+    ///
+    /// Returns the index of the font in `ui.fonts().fonts()` once the atlas is rebuilt. The first added font replaces
+    /// the built-in font as the default.
+    ///
+    /// # Errors
+    /// - Failed to load a font file
+    pub fn add(&mut self, resources: &Resources, face: &FontFace, merged: &[FontFace]) -> Result<usize> {
+        let faces = std::iter::once(face).chain(merged)
+            .map(|face| {
+                let data = face.resource_name.as_ref()
+                    .map(|name| resources.load_bytes(name)
+                        .map_err(|source| Error::Resource { name: name.clone(), source }))
+                    .transpose()?;
+                Ok(LoadedFace { face: face.clone(), data })
+            })
+            .collect::<Result<_>>()?;
+        self.fonts.push(faces);
+        self.built_scale = None;
+
+        Ok(self.fonts.len() - 1)
+    }
+
+    #[must_use]
+    pub fn needs_rebuild(&self, scale: f32) -> bool {
+        self.built_scale != Some(scale)
+    }
+
+    /// Replaces the fonts of `atlas`, rasterizing them at `scale` times their size
+    ///
+    /// The atlas texture has to be built and uploaded again afterwards.
+    pub fn build(&mut self, atlas: &mut FontAtlas, scale: f32) {
+        atlas.clear();
+        if self.fonts.is_empty() {
+            atlas.add_font(&[to_font_source(&FontFace::default(), None, scale, false)]);
+        }
+        for faces in &self.fonts {
+            let sources = faces.iter().enumerate()
+                .map(|(index, loaded)| to_font_source(&loaded.face, loaded.data.as_deref(), scale, index > 0))
+                .collect::<Vec<_>>();
+            atlas.add_font(&sources);
+        }
+        self.built_scale = Some(scale);
+    }
+}
+
+impl Default for Fonts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_font_source<'a>(face: &FontFace, data: Option<&'a [u8]>, scale: f32, merged: bool) -> FontSource<'a> {
+    let size_pixels = face.size * scale;
+    let config = FontConfig {
+        size_pixels,
+        glyph_ranges: face.glyph_ranges.to_imgui(),
+        // Icons merged into text are as wide as they're high, so they line up in lists and buttons
+        glyph_min_advance_x: if merged && face.glyph_ranges == GlyphRanges::Icons { size_pixels } else { 0f32 },
+        ..FontConfig::default()
+    };
+
+    match data {
+        Some(data) => FontSource::TtfData { data, size_pixels, config: Some(config) },
+        // The built-in bitmap font is only sharp without oversampling, which Dear ImGui only disables without a config
+        None => FontSource::DefaultFontData {
+            config: Some(FontConfig { oversample_h: 1, oversample_v: 1, pixel_snap_h: true, ..config }),
+        },
+    }
+}
@@ -0,0 +1,338 @@
+use std::cell::Cell;
+use std::fmt;
+
+use imgui::internal::RawCast;
+use imgui::{sys, BackendFlags, Io, Key, MouseButton, MouseCursor};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod, TextInputUtil};
+use sdl2::mouse::{self, Cursor, MouseUtil, SystemCursor};
+use sdl2::rect::Rect;
+use sdl2::video::Window;
+
+thread_local! {
+    /// Latest text cursor reported by Dear ImGui, which only passes it to a plain function pointer
+    static PLATFORM_IME_DATA: Cell<sys::ImGuiPlatformImeData> = Cell::new(sys::ImGuiPlatformImeData::default());
+}
+
+/// State of the input method editor, which composes e.g. CJK characters from several key presses
+#[derive(Debug, Default)]
+pub struct Ime {
+    /// Text being composed, which isn't committed to Dear ImGui yet
+    composition: String,
+    input_rect: Option<Rect>,
+    /// Keys pressed while composing, whose release is withheld from Dear ImGui along with the press
+    composed_keys: Vec<Keycode>,
+}
+
+/// SDL's system cursors for the cursor shapes Dear ImGui requests, e.g. over resize handles and text fields
+pub struct MouseCursors {
+    /// Indexed by [`MouseCursor`]
+    cursors: Vec<Cursor>,
+    current: Option<MouseCursor>,
+    captured: bool,
+}
+
+impl MouseCursors {
+    /// Creates the cursors, which requires SDL's video subsystem
+    ///
+    /// # Errors
+    /// - SDL failed to create a system cursor
+    pub fn new() -> Result<Self, String> {
+        let cursors = MouseCursor::VARIANTS.iter()
+            .map(|cursor| Cursor::from_system(to_system_cursor(*cursor)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { cursors, current: Some(MouseCursor::Arrow), captured: false })
+    }
+}
+
+impl fmt::Debug for MouseCursors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MouseCursors")
+            .field("current", &self.current)
+            .field("captured", &self.captured)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Declares the platform features [`update_mouse`] provides and installs the callback through which
+/// Dear ImGui reports where text is being entered
+pub fn init(io: &mut Io) {
+    io.backend_flags.insert(BackendFlags::HAS_MOUSE_CURSORS | BackendFlags::HAS_SET_MOUSE_POS);
+    io.set_platform_ime_data_fn = Some(set_platform_ime_data);
+}
+
+/// Queues the input of an SDL event in Dear ImGui, ignoring events it has no use for
+pub fn handle_event(io: &mut Io, ime: &mut Ime, event: &Event) {
+    match *event {
+        Event::MouseMotion { x, y, .. } => io.add_mouse_pos_event([x as f32, y as f32]),
+        Event::MouseButtonDown { mouse_btn, .. } => {
+            if let Some(button) = to_imgui_mouse_button(mouse_btn) {
+                io.add_mouse_button_event(button, true);
+            }
+        }
+        Event::MouseButtonUp { mouse_btn, .. } => {
+            if let Some(button) = to_imgui_mouse_button(mouse_btn) {
+                io.add_mouse_button_event(button, false);
+            }
+        }
+        Event::MouseWheel { precise_x, precise_y, .. } => {
+            // Dear ImGui scrolls left for positive horizontal values, while SDL reports scrolling right as positive
+            #[cfg(not(target_os = "emscripten"))]
+            let precise_x = -precise_x;
+            // Browsers report horizontal scrolling in pixels
+            #[cfg(target_os = "emscripten")]
+            let precise_x = -precise_x / 100f32;
+            io.add_mouse_wheel_event([precise_x, precise_y]);
+        }
+        // The input method consumes key presses while composing, e.g. Backspace removes composed characters.
+        // Releases are only withheld for those presses, as the key starting a composition is released while composing.
+        Event::KeyDown { keycode: Some(keycode), keymod, .. } => {
+            if ime.composition.is_empty() {
+                handle_key_event(io, keycode, keymod, true);
+            } else if !ime.composed_keys.contains(&keycode) {
+                ime.composed_keys.push(keycode);
+            }
+        }
+        Event::KeyUp { keycode: Some(keycode), keymod, .. } => {
+            if let Some(index) = ime.composed_keys.iter().position(|composed_key| *composed_key == keycode) {
+                ime.composed_keys.swap_remove(index);
+            } else {
+                handle_key_event(io, keycode, keymod, false);
+            }
+        }
+        Event::TextEditing { ref text, .. } => text.clone_into(&mut ime.composition),
+        Event::TextInput { ref text, .. } => {
+            ime.composition.clear();
+            for character in text.chars() {
+                io.add_input_character(character);
+            }
+        }
+        Event::Window { win_event, .. } => match win_event {
+            WindowEvent::FocusGained => add_focus_event(io, true),
+            WindowEvent::FocusLost => add_focus_event(io, false),
+            WindowEvent::Leave => io.add_mouse_pos_event([-f32::MAX, -f32::MAX]),
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+/// Starts SDL's text input while Dear ImGui wants text and moves the input method's candidate window to the cursor
+pub fn update_text_input(io: &Io, ime: &mut Ime, text_input: &TextInputUtil) {
+    if io.want_text_input && !text_input.is_active() {
+        text_input.start();
+    } else if !io.want_text_input && text_input.is_active() {
+        text_input.stop();
+        ime.composition.clear();
+        ime.input_rect = None;
+    }
+
+    let ime_data = PLATFORM_IME_DATA.with(Cell::get);
+    if io.want_text_input && ime_data.WantVisible {
+        let input_rect = Rect::new(
+            ime_data.InputPos.x as i32,
+            ime_data.InputPos.y as i32,
+            1,
+            ime_data.InputLineHeight as u32);
+        if ime.input_rect != Some(input_rect) {
+            text_input.set_rect(input_rect);
+            ime.input_rect = Some(input_rect);
+        }
+    }
+}
+
+/// Applies the cursor Dear ImGui requested, moves the mouse where navigation wants it and captures it while dragging
+pub fn update_mouse(
+    io: &mut Io,
+    cursor: Option<MouseCursor>,
+    mouse_cursors: &mut MouseCursors,
+    mouse: &MouseUtil,
+    window: &Window,
+) {
+    if io.want_set_mouse_pos {
+        mouse.warp_mouse_in_window(window, io.mouse_pos[0] as i32, io.mouse_pos[1] as i32);
+    }
+
+    // Keeps motion and button release events coming while dragging outside of the window, which browsers don't allow
+    #[cfg(not(target_os = "emscripten"))]
+    {
+        let dragging = io.mouse_down.iter().any(|down| *down);
+        if dragging != mouse_cursors.captured {
+            mouse.capture(dragging);
+            mouse_cursors.captured = dragging;
+        }
+    }
+
+    // Dear ImGui draws the cursor itself with `mouse_draw_cursor`, and requests no cursor to hide it
+    let cursor = if io.mouse_draw_cursor { None } else { cursor };
+    if cursor != mouse_cursors.current {
+        match cursor {
+            Some(cursor) => {
+                mouse_cursors.cursors[cursor as usize].set();
+                mouse.show_cursor(true);
+            }
+            None => mouse.show_cursor(false),
+        }
+        mouse_cursors.current = cursor;
+    }
+}
+
+unsafe extern "C" fn set_platform_ime_data(_viewport: *mut sys::ImGuiViewport, data: *mut sys::ImGuiPlatformImeData) {
+    PLATFORM_IME_DATA.with(|ime_data| ime_data.set(*data));
+}
+
+fn handle_key_event(io: &mut Io, keycode: Keycode, keymod: Mod, down: bool) {
+    io.add_key_event(Key::ModCtrl, keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD));
+    io.add_key_event(Key::ModShift, keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD));
+    io.add_key_event(Key::ModAlt, keymod.intersects(Mod::LALTMOD | Mod::RALTMOD));
+    io.add_key_event(Key::ModSuper, keymod.intersects(Mod::LGUIMOD | Mod::RGUIMOD));
+
+    if let Some(key) = to_imgui_key(keycode) {
+        io.add_key_event(key, down);
+    }
+}
+
+/// Releases all keys and buttons Dear ImGui still considers held when the window loses focus
+fn add_focus_event(io: &mut Io, focused: bool) {
+    unsafe { imgui::sys::ImGuiIO_AddFocusEvent(io.raw_mut(), focused) };
+}
+
+fn to_imgui_mouse_button(button: mouse::MouseButton) -> Option<MouseButton> {
+    match button {
+        mouse::MouseButton::Left => Some(MouseButton::Left),
+        mouse::MouseButton::Right => Some(MouseButton::Right),
+        mouse::MouseButton::Middle => Some(MouseButton::Middle),
+        mouse::MouseButton::X1 => Some(MouseButton::Extra1),
+        mouse::MouseButton::X2 => Some(MouseButton::Extra2),
+        mouse::MouseButton::Unknown => None,
+    }
+}
+
+fn to_system_cursor(cursor: MouseCursor) -> SystemCursor {
+    match cursor {
+        MouseCursor::Arrow => SystemCursor::Arrow,
+        MouseCursor::TextInput => SystemCursor::IBeam,
+        MouseCursor::ResizeAll => SystemCursor::SizeAll,
+        MouseCursor::ResizeNS => SystemCursor::SizeNS,
+        MouseCursor::ResizeEW => SystemCursor::SizeWE,
+        MouseCursor::ResizeNESW => SystemCursor::SizeNESW,
+        MouseCursor::ResizeNWSE => SystemCursor::SizeNWSE,
+        MouseCursor::Hand => SystemCursor::Hand,
+        MouseCursor::NotAllowed => SystemCursor::No,
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+fn to_imgui_key(keycode: Keycode) -> Option<Key> {
+    let key = match keycode {
+        Keycode::Tab => Key::Tab,
+        Keycode::Left => Key::LeftArrow,
+        Keycode::Right => Key::RightArrow,
+        Keycode::Up => Key::UpArrow,
+        Keycode::Down => Key::DownArrow,
+        Keycode::PageUp => Key::PageUp,
+        Keycode::PageDown => Key::PageDown,
+        Keycode::Home => Key::Home,
+        Keycode::End => Key::End,
+        Keycode::Insert => Key::Insert,
+        Keycode::Delete => Key::Delete,
+        Keycode::Backspace => Key::Backspace,
+        Keycode::Space => Key::Space,
+        Keycode::Return => Key::Enter,
+        Keycode::Escape => Key::Escape,
+        Keycode::LCtrl => Key::LeftCtrl,
+        Keycode::LShift => Key::LeftShift,
+        Keycode::LAlt => Key::LeftAlt,
+        Keycode::LGui => Key::LeftSuper,
+        Keycode::RCtrl => Key::RightCtrl,
+        Keycode::RShift => Key::RightShift,
+        Keycode::RAlt => Key::RightAlt,
+        Keycode::RGui => Key::RightSuper,
+        Keycode::Application => Key::Menu,
+        Keycode::Num0 => Key::Alpha0,
+        Keycode::Num1 => Key::Alpha1,
+        Keycode::Num2 => Key::Alpha2,
+        Keycode::Num3 => Key::Alpha3,
+        Keycode::Num4 => Key::Alpha4,
+        Keycode::Num5 => Key::Alpha5,
+        Keycode::Num6 => Key::Alpha6,
+        Keycode::Num7 => Key::Alpha7,
+        Keycode::Num8 => Key::Alpha8,
+        Keycode::Num9 => Key::Alpha9,
+        Keycode::A => Key::A,
+        Keycode::B => Key::B,
+        Keycode::C => Key::C,
+        Keycode::D => Key::D,
+        Keycode::E => Key::E,
+        Keycode::F => Key::F,
+        Keycode::G => Key::G,
+        Keycode::H => Key::H,
+        Keycode::I => Key::I,
+        Keycode::J => Key::J,
+        Keycode::K => Key::K,
+        Keycode::L => Key::L,
+        Keycode::M => Key::M,
+        Keycode::N => Key::N,
+        Keycode::O => Key::O,
+        Keycode::P => Key::P,
+        Keycode::Q => Key::Q,
+        Keycode::R => Key::R,
+        Keycode::S => Key::S,
+        Keycode::T => Key::T,
+        Keycode::U => Key::U,
+        Keycode::V => Key::V,
+        Keycode::W => Key::W,
+        Keycode::X => Key::X,
+        Keycode::Y => Key::Y,
+        Keycode::Z => Key::Z,
+        Keycode::F1 => Key::F1,
+        Keycode::F2 => Key::F2,
+        Keycode::F3 => Key::F3,
+        Keycode::F4 => Key::F4,
+        Keycode::F5 => Key::F5,
+        Keycode::F6 => Key::F6,
+        Keycode::F7 => Key::F7,
+        Keycode::F8 => Key::F8,
+        Keycode::F9 => Key::F9,
+        Keycode::F10 => Key::F10,
+        Keycode::F11 => Key::F11,
+        Keycode::F12 => Key::F12,
+        Keycode::Quote => Key::Apostrophe,
+        Keycode::Comma => Key::Comma,
+        Keycode::Minus => Key::Minus,
+        Keycode::Period => Key::Period,
+        Keycode::Slash => Key::Slash,
+        Keycode::Semicolon => Key::Semicolon,
+        Keycode::Equals => Key::Equal,
+        Keycode::LeftBracket => Key::LeftBracket,
+        Keycode::Backslash => Key::Backslash,
+        Keycode::RightBracket => Key::RightBracket,
+        Keycode::Backquote => Key::GraveAccent,
+        Keycode::CapsLock => Key::CapsLock,
+        Keycode::ScrollLock => Key::ScrollLock,
+        Keycode::NumLockClear => Key::NumLock,
+        Keycode::PrintScreen => Key::PrintScreen,
+        Keycode::Pause => Key::Pause,
+        Keycode::Kp0 => Key::Keypad0,
+        Keycode::Kp1 => Key::Keypad1,
+        Keycode::Kp2 => Key::Keypad2,
+        Keycode::Kp3 => Key::Keypad3,
+        Keycode::Kp4 => Key::Keypad4,
+        Keycode::Kp5 => Key::Keypad5,
+        Keycode::Kp6 => Key::Keypad6,
+        Keycode::Kp7 => Key::Keypad7,
+        Keycode::Kp8 => Key::Keypad8,
+        Keycode::Kp9 => Key::Keypad9,
+        Keycode::KpPeriod => Key::KeypadDecimal,
+        Keycode::KpDivide => Key::KeypadDivide,
+        Keycode::KpMultiply => Key::KeypadMultiply,
+        Keycode::KpMinus => Key::KeypadSubtract,
+        Keycode::KpPlus => Key::KeypadAdd,
+        Keycode::KpEnter => Key::KeypadEnter,
+        Keycode::KpEquals => Key::KeypadEqual,
+        _ => return None,
+    };
+
+    Some(key)
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use imgui::TextureId;
+
+use crate::texture::Texture;
+
+mod gl {
+    pub use gl::texture::*;
+}
+
+#[derive(Debug)]
+enum RegisteredTexture {
+    Owned(Texture),
+    /// Owned by a framebuffer or other code, which must unregister it before deleting it
+    Attachment(gl::TextureId),
+}
+
+/// Textures Dear ImGui's widgets can show, e.g. with `ui.image()`, referred to by registry specific ids.
+///
+/// The renderer only binds textures registered here, so an id which was never handed out or is already
+/// unregistered draws nothing instead of binding an arbitrary GL name. Ids aren't reused.
+#[derive(Debug)]
+pub struct Textures {
+    textures: HashMap<usize, RegisteredTexture>,
+    next_id: usize,
+}
+
+impl Textures {
+    #[must_use]
+    pub fn new() -> Self {
+        // Zero is left unused, so a default initialized id never refers to a texture
+        Self { textures: HashMap::new(), next_id: 1 }
+    }
+
+    /// Takes ownership of `texture`, which is deleted when it's unregistered or the registry is dropped
+    pub fn register(&mut self, texture: Texture) -> TextureId {
+        self.insert(RegisteredTexture::Owned(texture))
+    }
+
+    /// Registers a texture attached to a framebuffer, e.g. a render target or shadow map, without owning it
+    ///
+    /// The texture must be unregistered before it's deleted, as it would be bound by name otherwise.
+    pub fn register_attachment(&mut self, texture_id: gl::TextureId) -> TextureId {
+        self.insert(RegisteredTexture::Attachment(texture_id))
+    }
+
+    /// Releases the texture, deleting it if it was registered with [`Textures::register`]
+    pub fn unregister(&mut self, id: TextureId) {
+        self.textures.remove(&id.id());
+    }
+
+    /// GL texture to bind for `id`, `None` if it isn't registered
+    #[must_use]
+    pub fn get(&self, id: TextureId) -> Option<gl::TextureId> {
+        self.textures.get(&id.id()).map(|texture| match texture {
+            RegisteredTexture::Owned(texture) => unsafe { gl::TextureId::from_raw_handle(texture.handle()) },
+            RegisteredTexture::Attachment(texture_id) => *texture_id,
+        })
+    }
+
+    fn insert(&mut self, texture: RegisteredTexture) -> TextureId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.textures.insert(id, texture);
+        TextureId::new(id)
+    }
+}
+
+impl Default for Textures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,23 +1,38 @@
-use std::any::Any;
 use std::ffi::c_void;
 
-use imgui::{FontAtlas, TextureId};
-#[cfg(not(target_os = "emscripten"))]
+use imgui::internal::RawWrapper;
+use imgui::FontAtlas;
+#[cfg(not(gl_es))]
 use imgui::BackendFlags;
+use sdl2::clipboard::ClipboardUtil;
+use sdl2::event::Event;
+use sdl2::keyboard::TextInputUtil;
+use sdl2::mouse::MouseUtil;
+use sdl2::video::Window;
 
 use gl::sys::RawHandle;
 
+use crate::clipboard::Clipboard;
+use crate::draw_callbacks::{self, DrawCallbackArea, DrawCallbackId, DrawCallbacks};
+use crate::imgui_fonts::{self, FontFace, Fonts};
+use crate::imgui_input::{self, Ime, MouseCursors};
+use crate::imgui_textures::Textures;
 use crate::program::Program;
+use crate::resources::Resources;
 use crate::shader;
 use crate::shader::Shader;
+use crate::stream_buffer::{StreamBuffer, StreamMode};
 use crate::texture::Texture;
 
 mod gl {
     pub use gl::buffer::*;
-    pub use gl::capabilities::*;
+    pub use gl::pipeline::*;
     pub use gl::program::*;
     pub use gl::state::*;
+    pub use gl::state_cache::*;
+    pub use gl::state_snapshot::*;
     pub use gl::sys;
+    pub use gl::texture::*;
     pub use gl::vertex_array::*;
     pub use gl::vertex_attrib::*;
 }
@@ -25,96 +40,242 @@ mod gl {
 const IMGUI_VERTEX_SHADER_SOURCE: &str = include_str!("imgui.vert");
 const IMGUI_FRAGMENT_SHADER_SOURCE: &str = include_str!("imgui.frag");
 
+const VERTEX_STREAM_SIZE: usize = 1 << 20;
+const ELEMENT_STREAM_SIZE: usize = 1 << 18;
+/// Suballocation alignment within the stream buffers, large enough for both vertices and indices
+const STREAM_ALIGNMENT: usize = 4;
+
 #[derive(Debug)]
 pub struct Imgui {
     context: imgui::Context,
-    program: Program,
+    ime: Ime,
+    renderer: Renderer,
+    textures: Textures,
+    fonts: Fonts,
+    draw_callbacks: DrawCallbacks,
+}
+
+/// GL objects and state for drawing Dear ImGui's draw lists
+#[derive(Debug)]
+struct Renderer {
+    _program: Program,
+    pipeline_state: gl::PipelineState,
     proj_matrix_uniform_location: gl::UniformLocation,
     texture_uniform_location: gl::UniformLocation,
     vao: gl::VertexArrayId,
-    _vbo: gl::BufferId,
-    element_buffer_object: gl::BufferId,
+    vertex_stream: StreamBuffer,
+    element_stream: StreamBuffer,
 }
 
 type WindowDimension = [f32; 2];
-type MousePos = [f32; 2];
-type MouseButtonState = [bool; 2];
+type FramebufferScale = [f32; 2];
 
 impl Imgui {
+    /// Copied text goes to SDL's `clipboard`, or is kept in memory without it, see [`Clipboard`]
     #[must_use]
-    pub fn init() -> Self {
+    pub fn init(clipboard: Option<ClipboardUtil>) -> Self {
         let mut context = imgui::Context::create();
-        #[cfg(not(target_os = "emscripten"))]
+        // The layout is persisted through `load_ini_settings` and `take_ini_settings_to_save` instead of a file
+        context.set_ini_filename(None);
+        context.set_clipboard_backend(Clipboard::new(clipboard));
+        imgui_input::init(context.io_mut());
+        #[cfg(not(gl_es))]
         {
-            context.io_mut().backend_flags = BackendFlags::RENDERER_HAS_VTX_OFFSET;
+            context.io_mut().backend_flags.insert(BackendFlags::RENDERER_HAS_VTX_OFFSET);
+        }
+
+        let mut imgui = Self {
+            context,
+            ime: Ime::default(),
+            renderer: Renderer::new(),
+            textures: Textures::new(),
+            fonts: Fonts::new(),
+            draw_callbacks: DrawCallbacks::default(),
+        };
+        imgui.rebuild_font_atlas(1f32);
+
+        imgui
+    }
+
+    pub fn prepare(
+        &mut self,
+        window_dimension: WindowDimension,
+        framebuffer_scale: FramebufferScale,
+        delta_time: f32,
+    ) {
+        // Fonts are rasterized at the framebuffer's resolution, e.g. after moving the window to a HiDPI display
+        let font_scale = framebuffer_scale[0].max(framebuffer_scale[1]);
+        if self.fonts.needs_rebuild(font_scale) {
+            self.rebuild_font_atlas(font_scale);
         }
 
-        let _font_texture = generate_font_texture_from_atlas(context.fonts());
+        let io = self.context.io_mut();
+        io.display_size = window_dimension;
+        io.display_framebuffer_scale = framebuffer_scale;
+        io.delta_time = delta_time;
+    }
+
+    /// Loads a font from the resources, which the next [`Imgui::prepare`] rebuilds the font atlas with
+    ///
+    /// Returns the index of the font in `ui.fonts().fonts()`, see [`Fonts::add`].
+    ///
+    /// # Errors
+    /// - Failed to load a font file
+    pub fn add_font(&mut self, resources: &Resources, face: &FontFace, merged: &[FontFace]) -> Result<usize, imgui_fonts::Error> {
+        self.fonts.add(resources, face, merged)
+    }
+
+    /// Rasterizes the fonts at `scale` times their size and replaces the font texture
+    fn rebuild_font_atlas(&mut self, scale: f32) {
+        let atlas = self.context.fonts();
+        let previous_texture = atlas.tex_id;
+        self.fonts.build(atlas, scale);
+        atlas.tex_id = self.textures.register(generate_font_texture_from_atlas(atlas));
+        self.textures.unregister(previous_texture);
+        // Glyphs rasterized at a larger scale are drawn at their size in window coordinates
+        self.context.io_mut().font_global_scale = 1f32 / scale;
+    }
+
+    /// Restores window positions, sizes and collapsed states, which must happen before the first frame
+    pub fn load_ini_settings(&mut self, ini: &str) {
+        self.context.load_ini_settings(ini);
+    }
+
+    /// The layout in `.ini` format if it changed, which Dear ImGui reports a few seconds after e.g. moving a window
+    pub fn take_ini_settings_to_save(&mut self) -> Option<String> {
+        let io = self.context.io_mut();
+        if !io.want_save_ini_settings {
+            return None;
+        }
+        io.want_save_ini_settings = false;
+
+        let mut ini = String::new();
+        self.context.save_ini_settings(&mut ini);
+        Some(ini)
+    }
+
+    /// Forwards keyboard, mouse, text and focus input to Dear ImGui, which is applied by the next frame
+    pub fn handle_event(&mut self, event: &Event) {
+        imgui_input::handle_event(self.context.io_mut(), &mut self.ime, event);
+    }
+
+    /// Keeps SDL's text input and input method in sync with the text fields of the last rendered frame
+    pub fn update_text_input(&mut self, text_input: &TextInputUtil) {
+        imgui_input::update_text_input(self.context.io(), &mut self.ime, text_input);
+    }
+
+    /// Applies the mouse cursor and position requested by the last rendered frame
+    pub fn update_mouse(&mut self, mouse_cursors: &mut MouseCursors, mouse: &MouseUtil, window: &Window) {
+        let cursor = self.context.mouse_cursor();
+        imgui_input::update_mouse(self.context.io_mut(), cursor, mouse_cursors, mouse, window);
+    }
+
+    /// How the draw lists are streamed to the GPU, [`StreamMode::OrphaningFallback`] if either stream fell back
+    #[must_use]
+    pub fn stream_mode(&self) -> StreamMode {
+        let modes = [self.renderer.vertex_stream.mode(), self.renderer.element_stream.mode()];
+        if modes.contains(&StreamMode::OrphaningFallback) {
+            StreamMode::OrphaningFallback
+        } else {
+            modes[0]
+        }
+    }
+
+    /// Textures which windows can show by their id, e.g. with `ui.image()`, including the font atlas
+    pub fn textures_mut(&mut self) -> &mut Textures {
+        &mut self.textures
+    }
+
+    /// Registers custom GL rendering, which windows place with [`add_draw_callback`](crate::draw_callbacks::add_draw_callback)
+    pub fn register_draw_callback<F>(&mut self, callback: F) -> DrawCallbackId
+        where
+            F: FnMut(DrawCallbackArea) + 'static,
+    {
+        self.draw_callbacks.register(callback)
+    }
+
+    pub fn render<F>(&mut self, mut callback: F)
+        where
+            F: FnMut(&imgui::Ui),
+    {
+        let ui = self.context.frame();
+        callback(ui);
+        let draw_data = self.context.render();
+        self.renderer.render(draw_data, &self.textures, &mut self.draw_callbacks);
+    }
+}
+
+impl Renderer {
+    fn new() -> Self {
         let program = create_program();
-        let vertex_buffer_object = gl::gen_buffer();
-        let element_buffer_object = gl::gen_buffer();
-        gl::bind_buffer(gl::ARRAY_BUFFER, vertex_buffer_object);
-        gl::bind_buffer(gl::ELEMENT_ARRAY_BUFFER, element_buffer_object);
+        let vertex_stream = StreamBuffer::new(gl::ARRAY_BUFFER, VERTEX_STREAM_SIZE);
         let vao = gl::gen_vertex_array();
 
-        let vtx_size = std::mem::size_of::<imgui::DrawVert>();
-        program.set_used();
         gl::bind_vertex_array(vao);
+        let element_stream = StreamBuffer::new(gl::ELEMENT_ARRAY_BUFFER, ELEMENT_STREAM_SIZE);
         gl::enable_vertex_attrib_array(0);
         gl::enable_vertex_attrib_array(1);
         gl::enable_vertex_attrib_array(2);
-        gl::vertex_attrib_pointer(0, gl::SIZE_2, gl::FLOAT, false, vtx_size, 0);
-        gl::vertex_attrib_pointer(1, gl::SIZE_2, gl::FLOAT, false, vtx_size, 2 * std::mem::size_of::<f32>());
-        gl::vertex_attrib_pointer(2, gl::SIZE_3, gl::UNSIGNED_BYTE, true, vtx_size, 4 * std::mem::size_of::<f32>());
         gl::bind_vertex_array(gl::NO_VERTEX_ARRAY);
         gl::bind_buffer(gl::ARRAY_BUFFER, gl::NO_BUFFER);
-        gl::bind_buffer(gl::ELEMENT_ARRAY_BUFFER, gl::NO_BUFFER);
 
         let proj_matrix_uniform_location = gl::uniform_location(program.id(), "ProjMtx");
         let texture_uniform_location = gl::uniform_location(program.id(), "Texture");
+        let pipeline_state = gl::PipelineState {
+            blend: gl::BlendState::ALPHA_BLENDING,
+            rasterizer: gl::RasterizerState {
+                scissor_enabled: true,
+                ..gl::RasterizerState::default()
+            },
+            ..gl::PipelineState::new(program.id())
+        };
 
         Self {
-            context,
-            program,
+            _program: program,
+            pipeline_state,
             proj_matrix_uniform_location,
             texture_uniform_location,
             vao,
-            _vbo: vertex_buffer_object,
-            element_buffer_object,
+            vertex_stream,
+            element_stream,
         }
     }
 
-    pub fn prepare(
-        &mut self,
-        window_dimension: WindowDimension,
-        mouse_pos: MousePos,
-        mouse_button_state: MouseButtonState,
-        chars: &mut Vec<char>,
-    ) {
-        let io = self.context.io_mut();
-        io.display_size = window_dimension;
-        io.delta_time = 1f32 / 60f32;
+    /// Applies the pipeline, viewport, uniforms and bindings which the draw lists are drawn with
+    fn setup_render_state(&self, framebuffer_size: (usize, usize), projection: &nalgebra_glm::Mat4) {
+        self.pipeline_state.apply();
 
-        io.mouse_pos = mouse_pos;
-        io.mouse_down[0] = mouse_button_state[0];
-        io.mouse_down[1] = mouse_button_state[1];
+        gl::viewport((0, 0), framebuffer_size);
+        gl::uniform(self.texture_uniform_location, 0);
+        gl::uniform_matrix4(self.proj_matrix_uniform_location, false, nalgebra_glm::value_ptr(projection));
+        gl::bind_vertex_array(self.vao);
+        self.vertex_stream.bind();
+        self.element_stream.bind();
+        gl::active_texture(gl::texture_unit(0));
+    }
 
-        for char in chars.iter() {
-            io.add_input_character(*char);
-        }
-        chars.truncate(0);
+    /// Sets up the render state again after other code changed it, possibly bypassing the state cache
+    fn reset_render_state(&self, framebuffer_size: (usize, usize), projection: &nalgebra_glm::Mat4) {
+        gl::invalidate();
+        gl::invalidate_pipeline_state();
+        self.setup_render_state(framebuffer_size, projection);
     }
 
-    /// # Panics
-    /// - Unimplemented draw command
-    #[allow(clippy::too_many_lines)]
-    pub fn render<F>(&mut self, mut callback: F)
-        where
-            F: FnMut(&imgui::Ui),
-    {
-        let ui = self.context.frame();
-        callback(ui);
-        let draw_data = self.context.render();
+    fn render(&mut self, draw_data: &imgui::DrawData, textures: &Textures, draw_callbacks: &mut DrawCallbacks) {
+        // Draw data is in window coordinates, which are scaled to pixels on HiDPI displays
+        let [display_pos_x, display_pos_y] = draw_data.display_pos;
+        let [display_size_w, display_size_h] = draw_data.display_size;
+        let [scale_x, scale_y] = draw_data.framebuffer_scale;
+        let frame_buffer_width = display_size_w * scale_x;
+        let frame_buffer_height = display_size_h * scale_y;
+        // Windows are hidden in their first frame while auto-sizing, which may leave nothing to draw
+        if frame_buffer_width <= 0f32 || frame_buffer_height <= 0f32 || draw_data.draw_lists_count() == 0 {
+            return;
+        }
+        let framebuffer_size = (frame_buffer_width as usize, frame_buffer_height as usize);
+        let to_framebuffer_area = |clip_rect: [f32; 4]| {
+            clip_rect_to_framebuffer_area(clip_rect, draw_data.display_pos, draw_data.framebuffer_scale, framebuffer_size)
+        };
 
         let message = "ImGui Rendering";
         // Message length is guaranteed to not exceed 31bits
@@ -130,23 +291,8 @@ impl Imgui {
             */
         }
 
-        gl::enable(gl::BLEND);
-        gl::blend_equation(gl::FUNC_ADD);
-        gl::blend_func_separate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
-        gl::disable(gl::CULL_FACE);
-        gl::disable(gl::DEPTH_TEST);
-        gl::disable(gl::STENCIL_TEST);
-        gl::enable(gl::SCISSOR_TEST);
-        #[cfg(not(target_os = "emscripten"))]
-        unsafe { gl::sys::PolygonMode(gl::sys::FRONT_AND_BACK, gl::sys::FILL) };
-
-        let [display_pos_x, display_pos_y] = draw_data.display_pos;
-        let [display_size_w, display_size_h] = draw_data.display_size;
-        let frame_buffer_width = display_pos_x + display_size_w;
-        let frame_buffer_height = display_pos_y + display_size_h;
-        gl::viewport(
-            (display_pos_x as _, display_pos_y as _),
-            (display_size_w as _, display_size_h as _));
+        // The caller's state is queried rather than assumed, since it may have been changed bypassing the state cache
+        let caller_state = gl::StateSnapshot::capture();
         let ortho = nalgebra_glm::ortho(
             display_pos_x,
             display_pos_x + display_size_w,
@@ -155,101 +301,135 @@ impl Imgui {
             -1f32,
             1f32,
         );
-        self.program.set_used();
-        gl::uniform(self.texture_uniform_location, 0);
-        gl::uniform_matrix4(self.proj_matrix_uniform_location, false, nalgebra_glm::value_ptr(&ortho));
-        gl::bind_vertex_array(self.vao);
-        gl::bind_buffer(gl::ARRAY_BUFFER, self._vbo);
-        gl::bind_buffer(gl::ELEMENT_ARRAY_BUFFER, self.element_buffer_object);
-        unsafe {
-            gl::sys::ActiveTexture(gl::sys::TEXTURE0);
-        }
+        self.setup_render_state(framebuffer_size, &ortho);
+
+        let draw_list_padding = draw_data.draw_lists_count() * STREAM_ALIGNMENT;
+        self.vertex_stream.reserve(
+            draw_data.total_vtx_count as usize * std::mem::size_of::<imgui::DrawVert>() + draw_list_padding);
+        self.element_stream.reserve(
+            draw_data.total_idx_count as usize * std::mem::size_of::<imgui::DrawIdx>() + draw_list_padding);
         for draw_list in draw_data.draw_lists() {
             let vtx_buffer = draw_list.vtx_buffer();
             let idx_buffer = draw_list.idx_buffer();
 
-            gl::buffer_data(gl::ARRAY_BUFFER, vtx_buffer, gl::STREAM_DRAW);
-            gl::buffer_data(gl::ELEMENT_ARRAY_BUFFER, idx_buffer, gl::STREAM_DRAW);
+            let vertex_stream_offset = self.vertex_stream.push(vtx_buffer, STREAM_ALIGNMENT)
+                .expect("Dear ImGui vertex stream exhausted despite reservation");
+            let element_stream_offset = self.element_stream.push(idx_buffer, STREAM_ALIGNMENT)
+                .expect("Dear ImGui element stream exhausted despite reservation");
+            set_vertex_attrib_pointers(vertex_stream_offset);
 
             for command in draw_list.commands() {
                 match command {
                     imgui::DrawCmd::Elements { count, cmd_params } => {
-                        let clip_rect = cmd_params.clip_rect;
-                        let clip_rect = [
-                            clip_rect[0] - display_pos_x,
-                            clip_rect[1] - display_pos_y,
-                            clip_rect[2] - display_pos_x,
-                            clip_rect[3] - display_pos_y,
-                        ];
+                        let Some(area) = to_framebuffer_area(cmd_params.clip_rect) else {
+                            continue;
+                        };
+                        let Some(texture_id) = textures.get(cmd_params.texture_id) else {
+                            continue;
+                        };
+                        gl::scissor(area.position, area.size);
 
                         let vtx_offset = cmd_params.vtx_offset;
-                        let idx_offset = cmd_params.idx_offset * std::mem::size_of::<imgui::DrawIdx>();
-                        if clip_rect[0] < frame_buffer_width
-                            && clip_rect[1] < frame_buffer_height
-                            && clip_rect[2] >= 0f32
-                            && clip_rect[3] >= 0f32
-                        {
-                            gl::scissor(
-                                (clip_rect[0] as _, (frame_buffer_height - clip_rect[3]) as _),
-                                ((clip_rect[2] - clip_rect[0]) as _, (clip_rect[3] - clip_rect[1]) as _));
-
-                            unsafe {
-                                gl::sys::BindTexture(
-                                    gl::sys::TEXTURE_2D,
-                                    gl::sys::types::GLuint::try_from(cmd_params.texture_id.id())
-                                        .unwrap_unchecked(),
-                                );
-                            }
-                            let gl_type = match std::mem::size_of::<imgui::DrawIdx>() {
-                                2 => gl::sys::UNSIGNED_SHORT,
-                                _ => gl::sys::UNSIGNED_INT,
-                            };
-                            #[cfg(not(target_os = "emscripten"))]
-                            unsafe {
-                                gl::sys::DrawElementsBaseVertex(
-                                    gl::sys::TRIANGLES,
-                                    gl::sys::types::GLsizei::try_from(count).unwrap_unchecked(),
-                                    gl_type,
-                                    idx_offset as *const c_void,
-                                    vtx_offset as gl::sys::types::GLint,
-                                );
-                            }
-                            #[cfg(target_os = "emscripten")]
-                            unsafe {
-                                gl::sys::DrawElements(
-                                    gl::sys::TRIANGLES,
-                                    gl::sys::types::GLsizei::try_from(count).unwrap_unchecked(),
-                                    gl_type,
-                                    idx_offset as *const c_void);
-                            }
+                        let idx_offset = element_stream_offset + cmd_params.idx_offset * std::mem::size_of::<imgui::DrawIdx>();
+                        gl::bind_texture(gl::TEXTURE_2D, texture_id);
+                        let gl_type = match std::mem::size_of::<imgui::DrawIdx>() {
+                            2 => gl::sys::UNSIGNED_SHORT,
+                            _ => gl::sys::UNSIGNED_INT,
+                        };
+                        #[cfg(not(gl_es))]
+                        unsafe {
+                            gl::sys::DrawElementsBaseVertex(
+                                gl::sys::TRIANGLES,
+                                gl::sys::types::GLsizei::try_from(count).unwrap_unchecked(),
+                                gl_type,
+                                idx_offset as *const c_void,
+                                vtx_offset as gl::sys::types::GLint,
+                            );
+                        }
+                        #[cfg(gl_es)]
+                        unsafe {
+                            gl::sys::DrawElements(
+                                gl::sys::TRIANGLES,
+                                gl::sys::types::GLsizei::try_from(count).unwrap_unchecked(),
+                                gl_type,
+                                idx_offset as *const c_void);
                         }
                     }
-                    x => {
-                        panic!("Unimplemented! {:?}", x.type_id());
+                    imgui::DrawCmd::ResetRenderState => {
+                        self.reset_render_state(framebuffer_size, &ortho);
+                        set_vertex_attrib_pointers(vertex_stream_offset);
+                    }
+                    imgui::DrawCmd::RawCallback { callback, raw_cmd } => {
+                        // Dear ImGui's draw lists own their commands until the next frame starts
+                        let command = unsafe { &*raw_cmd };
+                        if !draw_callbacks::is_draw_callback(command) {
+                            // Foreign callbacks restore the render state by adding `ResetRenderState` themselves
+                            unsafe { callback(draw_list.raw(), raw_cmd) };
+                            continue;
+                        }
+
+                        let clip_rect = command.ClipRect;
+                        let Some(area) = to_framebuffer_area([clip_rect.x, clip_rect.y, clip_rect.z, clip_rect.w]) else {
+                            continue;
+                        };
+                        gl::viewport(area.position, area.size);
+                        gl::scissor(area.position, area.size);
+                        draw_callbacks.invoke(command, area);
+                        self.reset_render_state(framebuffer_size, &ortho);
+                        set_vertex_attrib_pointers(vertex_stream_offset);
                     }
                 }
             }
         }
 
-        gl::disable(gl::BLEND);
-        gl::enable(gl::CULL_FACE);
-        gl::disable(gl::SCISSOR_TEST);
+        self.vertex_stream.finish_frame();
+        self.element_stream.finish_frame();
+        caller_state.restore();
         /*
                     gl::PopDebugGroup();
         */
     }
 }
 
+/// Converts a clip rectangle from Dear ImGui's display coordinates to framebuffer pixels, `None` if it's not visible
+fn clip_rect_to_framebuffer_area(
+    clip_rect: [f32; 4],
+    display_pos: [f32; 2],
+    framebuffer_scale: [f32; 2],
+    framebuffer_size: (usize, usize),
+) -> Option<DrawCallbackArea> {
+    let (width, height) = (framebuffer_size.0 as f32, framebuffer_size.1 as f32);
+    let min_x = ((clip_rect[0] - display_pos[0]) * framebuffer_scale[0]).max(0f32);
+    let min_y = ((clip_rect[1] - display_pos[1]) * framebuffer_scale[1]).max(0f32);
+    let max_x = ((clip_rect[2] - display_pos[0]) * framebuffer_scale[0]).min(width);
+    let max_y = ((clip_rect[3] - display_pos[1]) * framebuffer_scale[1]).min(height);
+    if min_x >= max_x || min_y >= max_y {
+        return None;
+    }
+
+    // GL's origin is at the bottom left, Dear ImGui's at the top left
+    Some(DrawCallbackArea {
+        position: (min_x as usize, (height - max_y) as usize),
+        size: ((max_x - min_x) as usize, (max_y - min_y) as usize),
+    })
+}
+
+/// Points the vertex attributes at the draw list starting `offset` bytes into the bound array buffer
+fn set_vertex_attrib_pointers(offset: usize) {
+    let vtx_size = std::mem::size_of::<imgui::DrawVert>();
+    gl::vertex_attrib_pointer(0, gl::SIZE_2, gl::FLOAT, false, vtx_size, offset);
+    gl::vertex_attrib_pointer(1, gl::SIZE_2, gl::FLOAT, false, vtx_size, offset + 2 * std::mem::size_of::<f32>());
+    gl::vertex_attrib_pointer(2, gl::SIZE_3, gl::UNSIGNED_BYTE, true, vtx_size, offset + 4 * std::mem::size_of::<f32>());
+}
+
 fn generate_font_texture_from_atlas(font_atlas: &mut FontAtlas) -> Texture {
     let font_atlas_texture = &mut font_atlas.build_rgba32_texture();
-    let font_texture = Texture::from_raw(
+    Texture::from_raw(
         font_atlas_texture.data,
         font_atlas_texture.width as usize,
         font_atlas_texture.height as usize,
     )
-        .expect("Failed to create font texture for Dear ImGui");
-    font_atlas.tex_id = TextureId::new(font_texture.handle() as usize);
-    font_texture
+        .expect("Failed to create font texture for Dear ImGui")
 }
 
 fn create_program() -> Program {
@@ -1,41 +1,84 @@
 extern crate anyhow;
 extern crate sdl2;
 
+use std::cell::RefCell;
 #[cfg(target_os = "emscripten")]
 use std::ffi::CStr;
 use std::path::Path;
+use std::rc::Rc;
 
 use anyhow::Result;
+use sdl2::event::WindowEvent;
+use sdl2::keyboard::Keycode;
 use sdl2::mouse::MouseButton;
 use sdl2::video::GLProfile;
+#[cfg(not(target_os = "emscripten"))]
+use sdl2::video::SwapInterval;
+#[cfg(not(target_os = "emscripten"))]
+use sdl2::VideoSubsystem;
 
+use crate::capture::{CaptureFormat, CaptureSettings, FrameCapture};
+use crate::draw_callbacks::add_draw_callback;
+use crate::frame_timer::FrameTimer;
+use crate::gpu_profiler::GpuProfiler;
+use crate::imgui_fonts::FontFace;
+use crate::imgui_wrapper::Imgui;
+#[cfg(not(target_os = "emscripten"))]
+use crate::headless::HeadlessContext;
+use crate::imgui_input::MouseCursors;
 use crate::key_codes::KeyCodes;
 use crate::mouse_buttons::MouseButtons;
-use crate::program::Program;
-use crate::shader::{Kind, Shader};
+use crate::readback::Image;
+use crate::render_target::RenderTarget;
+use crate::scene::Scene;
+use crate::screenshot::Screenshots;
+use crate::stream_buffer::StreamMode;
+use crate::settings::{Settings, SettingsStorage};
+use crate::texture::Texture;
 
 mod gl {
-    pub use gl::buffer::*;
+    pub use gl::context_info::*;
     pub use gl::load_with;
-    pub use gl::program::*;
-    pub use gl::rendering::*;
     pub use gl::state::*;
+    pub use gl::state_cache::*;
     pub use gl::sys;
-    pub use gl::vertex_array::*;
-    pub use gl::vertex_attrib::*;
 }
 
-#[cfg(target_os = "emscripten")]
+#[cfg(any(target_os = "emscripten", test))]
 pub mod emscripten;
+mod args;
 mod resources;
 mod shader;
 mod program;
+mod stream_buffer;
 mod imgui_wrapper;
+mod imgui_fonts;
+mod imgui_input;
+mod imgui_textures;
+mod clipboard;
+mod draw_callbacks;
 mod texture;
+mod gpu_profiler;
+#[cfg(all(test, not(target_os = "emscripten")))]
+mod golden;
+#[cfg(not(target_os = "emscripten"))]
+mod headless;
+mod scene;
+mod readback;
+mod render_target;
+mod screenshot;
+mod settings;
+mod capture;
+mod frame_timer;
 mod key_codes;
 mod mouse_buttons;
 
 pub fn main() -> Result<()> {
+    #[cfg(not(target_os = "emscripten"))]
+    if let Some(frames) = headless_frames() {
+        return run_headless(frames);
+    }
+
     #[cfg(target_os = "emscripten")]
         let hint = unsafe { CStr::from_ptr(sdl2::sys::SDL_HINT_EMSCRIPTEN_KEYBOARD_ELEMENT.as_ptr() as *const _) }
         .to_str()
@@ -43,74 +86,93 @@ pub fn main() -> Result<()> {
         .expect("Failed to set emscripten keyboard element for SDL");
     #[cfg(target_os = "emscripten")]
     sdl2::hint::set("SDL_EMSCRIPTEN_ASYNCIFY", "1");
+    // Dear ImGui can't show text that is still being composed, so the input method draws it instead
+    sdl2::hint::set("SDL_IME_SHOW_UI", "1");
+    let settings_storage = SettingsStorage::from_args();
+    let mut settings = Settings::load(&settings_storage);
+    let mut saved_settings = settings.clone();
     let sdl_context = sdl2::init()
         .expect("Failed to initialize SDL2");
     let video_subsystem = sdl_context.video()
         .expect("Failed to initialize SDL video subsystem");
     let _gl_attr = {
         let gl_attr = video_subsystem.gl_attr();
-        gl_attr.set_context_profile(GLProfile::GLES);
-        gl_attr.set_context_minor_version(0);
-        gl_attr.set_context_major_version(3);
+        // The context matches the generated bindings, GLES 3.0 for `gl_es` and GL 4.5 core otherwise
+        if cfg!(gl_es) {
+            gl_attr.set_context_profile(GLProfile::GLES);
+            gl_attr.set_context_major_version(3);
+            gl_attr.set_context_minor_version(0);
+        } else {
+            gl_attr.set_context_profile(GLProfile::Core);
+            gl_attr.set_context_major_version(4);
+            gl_attr.set_context_minor_version(5);
+        }
         gl_attr
     };
-    let window = video_subsystem.window("My Window", 500, 500)
+    // Emscripten resizes a resizable window's canvas along with the browser window
+    let window = video_subsystem.window("My Window", settings.window_size.0, settings.window_size.1)
         .opengl()
+        .resizable()
+        .allow_highdpi()
         .build()
         .expect("Failed to create window!");
     let _gl_context = window.gl_create_context()
         .expect("Failed to create OpenGL context");
     gl::load_with(|s| video_subsystem.gl_get_proc_address(s).cast::<std::ffi::c_void>());
+    print_context_info();
+    #[cfg(not(target_os = "emscripten"))]
+    set_vsync(&video_subsystem, settings.vsync);
 
     let mut event_pump = sdl_context.event_pump()
         .expect("Failed to retrieve event pump");
+    let text_input = video_subsystem.text_input();
+    let mouse = sdl_context.mouse();
+    let mut mouse_cursors = MouseCursors::new()
+        .expect("Failed to create mouse cursors");
 
     let resource = resources::Resources::from_relative_exe_path(Path::new("assets"))?;
 
-    let vertex_buffer = initialize_vertices();
-    let vertex_shader = {
-        let src = resource.load_string("vertex.glsl")?;
-        Shader::from_source(src.as_str(), Kind::Vertex)?
-    };
-    let fragmnet_shader = {
-        let src = resource.load_string("fragment.glsl")?;
-        Shader::from_source(src.as_str(), Kind::Fragment)?
-    };
-
-    let program = Program::from_shaders(&[
-        &vertex_shader, &fragmnet_shader])?;
-
-    gl::viewport((0, 0), (500, 500));
-
-    let vao = gl::gen_vertex_array();
-    gl::bind_vertex_array(vao);
-    gl::bind_buffer(gl::ARRAY_BUFFER, vertex_buffer);
-    gl::enable_vertex_attrib_array(0);
-    gl::enable_vertex_attrib_array(1);
+    let scene = Rc::new(Scene::new(&resource)?);
+    // Declared before Dear ImGui, so its registration is dropped before the texture is deleted
+    let render_target = RenderTarget::new((160, 120))?;
 
-    gl::vertex_attrib_pointer(0, gl::SIZE_2, gl::FLOAT, false, 5 * std::mem::size_of::<f32>(), 0);
-    gl::vertex_attrib_pointer(1, gl::SIZE_3, gl::FLOAT, false, 5 * std::mem::size_of::<f32>(), 2 * std::mem::size_of::<f32>());
-    gl::bind_vertex_array(gl::NO_VERTEX_ARRAY);
-
-    let mut imgui_context = imgui_wrapper::Imgui::init();
+    // Browsers are served through the asynchronous clipboard API instead
+    let clipboard = (!cfg!(target_os = "emscripten")).then(|| video_subsystem.clipboard());
+    let mut imgui_context = Imgui::init(clipboard);
+    report_stream_fallback(&imgui_context);
+    if let Some(ini) = settings_storage.load_imgui_ini() {
+        imgui_context.load_ini_settings(&ini);
+    }
+    if let Some((face, icons)) = FontFace::from_args() {
+        imgui_context.add_font(&resource, &face, icons.as_slice())?;
+    }
+    // Shared with the preview, so it advances on the fixed timestep while capturing, too
+    let frame_timer = Rc::new(RefCell::new(FrameTimer::new()));
+    let uncorrected_preview = {
+        let scene = Rc::clone(&scene);
+        let frame_timer = Rc::clone(&frame_timer);
+        imgui_context.register_draw_callback(move |_| scene.draw(1f32, &frame_timer.borrow()))
+    };
+    let render_target_texture = imgui_context.textures_mut().register_attachment(render_target.color_texture());
+    let mut gpu_profiler = GpuProfiler::new();
 
+    let mut window_size = window.size();
+    let mut drawable_size = window.drawable_size();
     let mut mouse_buttons = MouseButtons::default();
     let mut key_codes = KeyCodes::default();
-    let mut mouse_pos = (0, 0);
-    let mut chars: Vec<char> = Vec::new();
-    let mut gamma = 1f32;
+    let mut state_cache_enabled = gl::state_cache_enabled();
+    let mut screenshots = Screenshots::new(Screenshots::directory_from_args());
+    let mut screenshot_requested = false;
+    let mut screenshot_thumbnail = None;
+    let (mut capture_settings, capture_from_start) = CaptureSettings::from_args();
+    let mut capture = capture_from_start.then(|| FrameCapture::start(&capture_settings)).transpose()?;
+    let mut capture_toggled = false;
 
     let mut main_loop = || {
         for event in event_pump.poll_iter() {
             use sdl2::event::Event;
+            imgui_context.handle_event(&event);
             match event {
-                Event::MouseMotion { x, y, .. } => {
-                    mouse_pos = (
-                        // This is ok - Mouse coordinates shouldn't reach numbers which overflow 16bit
-                        i16::try_from(x).unwrap_or(0),
-                        i16::try_from(y).unwrap_or(0),
-                    );
-                }
                 Event::MouseButtonDown { mouse_btn, .. } => mouse_buttons[mouse_btn] = true,
                 Event::MouseButtonUp { mouse_btn, .. } => mouse_buttons[mouse_btn] = false,
                 Event::KeyDown {
@@ -118,57 +180,170 @@ pub fn main() -> Result<()> {
                     ..
                 } => {
                     key_codes[keycode] = true;
-
-                    let keycode = keycode as u32;
-                    if (32..512).contains(&keycode) {
-                        chars.push(char::from_u32(keycode).unwrap());
+                    if keycode == Keycode::F12 {
+                        screenshot_requested = true;
                     }
                 }
                 Event::KeyUp {
                     keycode: Some(keycode),
                     ..
                 } => key_codes[keycode] = false,
+                Event::Window {
+                    win_event: WindowEvent::Resized(..) | WindowEvent::SizeChanged(..),
+                    ..
+                } => {
+                    window_size = window.size();
+                    drawable_size = window.drawable_size();
+                    settings.window_size = window_size;
+                }
                 Event::Quit { .. } => return false,
                 _ => {}
             }
         }
 
+        // Captured frames advance on a fixed timestep, so they don't depend on how long capturing takes
+        match &capture {
+            Some(capture) => frame_timer.borrow_mut().tick_fixed(capture.delta_time()),
+            None => frame_timer.borrow_mut().tick(),
+        }
+        let frame_timer = frame_timer.borrow();
+
+        let framebuffer_size = (drawable_size.0 as usize, drawable_size.1 as usize);
+
         imgui_context.prepare(
-            [500f32, 500f32],
-            [mouse_pos.0.into(), mouse_pos.1.into()],
-            [
-                mouse_buttons[MouseButton::Left],
-                mouse_buttons[MouseButton::Right],
-            ],
-            &mut chars,
+            [window_size.0 as f32, window_size.1 as f32],
+            framebuffer_scale(window_size, drawable_size),
+            frame_timer.delta_time(),
         );
 
-        gl::clear_color(0xFF000000);
-        gl::clear(gl::COLOR);
+        let state_cache_stats = gl::state_cache_stats();
+        gl::reset_state_cache_stats();
+        gpu_profiler.begin_frame();
 
-        gl::bind_vertex_array(vao);
-        program.set_used();
+        gpu_profiler.scope("Render target", |_| render_target.render(|| scene.draw(settings.gamma, &frame_timer)));
+        gl::viewport((0, 0), framebuffer_size);
+        gpu_profiler.scope("Scene", |_| scene.draw(settings.gamma, &frame_timer));
 
-        let uniform_location = gl::uniform_location(program.id(), "gamma");
-        gl::uniform(uniform_location, gamma);
-        gl::draw_arrays(gl::TRIANGLES, 0, 3);
+        gpu_profiler.scope("Dear ImGui", |gpu_profiler| {
+            imgui_context.render(|ui| {
+                ui.window("Settings")
+                    .always_auto_resize(true)
+                    .build(|| {
+                        ui.text(format!(
+                            "{:.1} fps ({:.2} ms), frame {}, {:.1} s",
+                            frame_timer.fps(),
+                            frame_timer.delta_time() * 1000f32,
+                            frame_timer.frame_count(),
+                            frame_timer.total_time()));
 
-        imgui_context.render(|ui| {
-            ui.window("Settings")
-                .save_settings(false)
-                .always_auto_resize(true)
-                .build(|| {
-                    ui.slider("Gamma", 0.5f32, 2.5f32, &mut gamma);
-                    if ui.button("Reset (1.0)") {
-                        gamma = 1f32;
-                    }
-                    ui.same_line();
-                    if ui.button("Reset (2.2)") {
-                        gamma = 2.2f32;
-                    }
-                });
+                        ui.slider("Gamma", 0.5f32, 2.5f32, &mut settings.gamma);
+                        if ui.button("Reset (1.0)") {
+                            settings.gamma = 1f32;
+                        }
+                        ui.same_line();
+                        if ui.button("Reset (2.2)") {
+                            settings.gamma = 2.2f32;
+                        }
+                        ui.text("Without gamma correction:");
+                        let preview_min = ui.cursor_screen_pos();
+                        let preview_size = [160f32, 120f32];
+                        ui.dummy(preview_size);
+                        add_draw_callback(
+                            ui,
+                            uncorrected_preview,
+                            preview_min,
+                            [preview_min[0] + preview_size[0], preview_min[1] + preview_size[1]]);
+                        ui.text("Render target:");
+                        let (target_width, target_height) = render_target.size();
+                        imgui::Image::new(render_target_texture, [target_width as f32, target_height as f32])
+                            .uv0([0f32, 1f32])
+                            .uv1([1f32, 0f32])
+                            .build(ui);
+
+                        // Browsers always present in sync with the display
+                        #[cfg(not(target_os = "emscripten"))]
+                        if ui.checkbox("VSync", &mut settings.vsync) {
+                            set_vsync(&video_subsystem, settings.vsync);
+                        }
+                        if ui.checkbox("State cache", &mut state_cache_enabled) {
+                            gl::set_state_cache_enabled(state_cache_enabled);
+                        }
+                        ui.text(format!(
+                            "GL state calls: {} issued, {} skipped",
+                            state_cache_stats.calls_issued,
+                            state_cache_stats.calls_skipped));
+
+                        if ui.button("Screenshot (F12)") {
+                            screenshot_requested = true;
+                        }
+                        ui.text(format!("Saved to {}", screenshots.directory().display()));
+                        if let Some((texture_id, size)) = screenshot_thumbnail {
+                            imgui::Image::new(texture_id, size).build(ui);
+                        }
+
+                        ui.separator();
+                        let mut capturing = capture.is_some();
+                        if ui.checkbox("Capture frames", &mut capturing) {
+                            capture_toggled = true;
+                        }
+                        if let Some(capture) = &capture {
+                            ui.text(format!(
+                                "{} frames at {} fps to {}",
+                                capture.frames_written(),
+                                capture_settings.frame_rate,
+                                capture_settings.output_path().display()));
+                        } else {
+                            ui.radio_button("PNG", &mut capture_settings.format, CaptureFormat::Png);
+                            ui.same_line();
+                            ui.radio_button("Y4M", &mut capture_settings.format, CaptureFormat::Y4m);
+                        }
+                    });
+                gpu_profiler.draw_ui(ui);
+            });
         });
 
+        gpu_profiler.end_frame();
+        imgui_context.update_text_input(&text_input);
+        imgui_context.update_mouse(&mut mouse_cursors, &mouse, &window);
+
+        if let Some(frame_capture) = &mut capture {
+            if let Err(error) = frame_capture.write_frame(framebuffer_size) {
+                eprintln!("Failed to capture frame: {error}");
+                capture_toggled = true;
+            }
+        }
+        if capture_toggled {
+            toggle_capture(&mut capture, &capture_settings);
+            capture_toggled = false;
+        }
+
+        if screenshot_requested {
+            screenshots.capture(framebuffer_size);
+            screenshot_requested = false;
+        }
+        match screenshots.write_finished() {
+            Ok(paths) => {
+                for path in paths {
+                    println!("Saved screenshot to {}", path.display());
+                }
+            }
+            Err(error) => eprintln!("Failed to save screenshot: {error}"),
+        }
+        if let Some(image) = screenshots.take_latest() {
+            update_thumbnail(&mut imgui_context, &mut screenshot_thumbnail, &image);
+        }
+
+        if let Some(ini) = imgui_context.take_ini_settings_to_save() {
+            if let Err(error) = settings_storage.save_imgui_ini(&ini) {
+                eprintln!("Failed to save window layout: {error}");
+            }
+        }
+        // Dragging a slider changes settings on every frame, they're written once it's released
+        if settings != saved_settings && !mouse_buttons[MouseButton::Left] {
+            save_settings(&settings, &settings_storage);
+            saved_settings = settings.clone();
+        }
+
         #[cfg(not(target_os = "emscripten"))]
         window.gl_swap_window();
 
@@ -196,22 +371,152 @@ pub fn main() -> Result<()> {
         emscripten::emscripten::sleep(16);
     }
 
+    if capture.is_some() {
+        toggle_capture(&mut capture, &capture_settings);
+    }
+    if settings != saved_settings {
+        save_settings(&settings, &settings_storage);
+    }
+
+    Ok(())
+}
+
+fn save_settings(settings: &Settings, storage: &SettingsStorage) {
+    if let Err(error) = settings.save(storage) {
+        eprintln!("Failed to save settings: {error}");
+    }
+}
+
+#[cfg(not(target_os = "emscripten"))]
+fn set_vsync(video_subsystem: &VideoSubsystem, vsync: bool) {
+    let interval = if vsync { SwapInterval::VSync } else { SwapInterval::Immediate };
+    if let Err(error) = video_subsystem.gl_set_swap_interval(interval) {
+        eprintln!("Failed to set swap interval: {error}");
+    }
+}
+
+/// Ratio of drawable pixels to window coordinates, which is above 1 on HiDPI displays
+fn framebuffer_scale(window_size: (u32, u32), drawable_size: (u32, u32)) -> [f32; 2] {
+    // Minimized windows may report a size of zero
+    if window_size.0 == 0 || window_size.1 == 0 {
+        return [1f32, 1f32];
+    }
+
+    [
+        drawable_size.0 as f32 / window_size.0 as f32,
+        drawable_size.1 as f32 / window_size.1 as f32,
+    ]
+}
+
+/// Replaces the texture shown in `thumbnail` with `image`, sized to fit the settings window
+fn update_thumbnail(imgui: &mut Imgui, thumbnail: &mut Option<(imgui::TextureId, [f32; 2])>, image: &Image) {
+    const THUMBNAIL_WIDTH: f32 = 160f32;
+
+    if let Some((texture_id, _)) = thumbnail.take() {
+        imgui.textures_mut().unregister(texture_id);
+    }
+    match Texture::from_raw(&image.pixels, image.width, image.height) {
+        Ok(texture) => {
+            let height = THUMBNAIL_WIDTH * image.height as f32 / image.width.max(1) as f32;
+            *thumbnail = Some((imgui.textures_mut().register(texture), [THUMBNAIL_WIDTH, height]));
+        }
+        Err(error) => eprintln!("Failed to create screenshot thumbnail: {error}"),
+    }
+}
+
+/// Starts capturing if `capture` is `None`, otherwise finishes the running capture
+fn toggle_capture(capture: &mut Option<FrameCapture>, settings: &CaptureSettings) {
+    let path = settings.output_path();
+    match capture.take() {
+        Some(running) => match running.finish() {
+            Ok(frames) => println!("Captured {frames} frames to {}", path.display()),
+            Err(error) => eprintln!("Failed to finish capture: {error}"),
+        },
+        None => match FrameCapture::start(settings) {
+            Ok(started) => *capture = Some(started),
+            Err(error) => eprintln!("Failed to start capture to {}: {error}", path.display()),
+        },
+    }
+}
+
+/// Renders `frames` frames without a window and exits, e.g. on CI machines without a display
+#[cfg(not(target_os = "emscripten"))]
+fn run_headless(frames: usize) -> Result<()> {
+    let headless_context = HeadlessContext::new((500, 500))?;
+    print_context_info();
+
+    let resource = resources::Resources::from_relative_exe_path(Path::new("assets"))?;
+    let scene = Scene::new(&resource)?;
+    let mut imgui_context = Imgui::init(None);
+    report_stream_fallback(&imgui_context);
+    let display_size = headless_context.size();
+    let (capture_settings, capture_requested) = CaptureSettings::from_args();
+    let mut capture = capture_requested.then(|| FrameCapture::start(&capture_settings)).transpose()?;
+    let mut frame_timer = FrameTimer::new();
+
+    for frame in 0..frames {
+        // Frames render as fast as possible, so time advances on a fixed timestep instead of the wall clock
+        frame_timer.tick_fixed(capture.as_ref().map_or(1f32 / 60f32, FrameCapture::delta_time));
+
+        headless_context.bind();
+        imgui_context.prepare(
+            [display_size.0 as f32, display_size.1 as f32],
+            [1f32, 1f32],
+            frame_timer.delta_time(),
+        );
+
+        scene.draw(1f32, &frame_timer);
+        imgui_context.render(|ui| {
+            ui.window("Headless")
+                .save_settings(false)
+                .always_auto_resize(true)
+                .build(|| ui.text(format!("Frame {} of {frames}", frame + 1)));
+        });
+
+        if let Some(capture) = &mut capture {
+            capture.write_frame(display_size)?;
+        }
+    }
+    if let Some(capture) = capture {
+        let frames = capture.finish()?;
+        println!("Captured {frames} frames to {}", capture_settings.output_path().display());
+    }
+
+    // Reading back waits for rendering to finish
+    let path = Screenshots::directory_from_args().join("headless.png");
+    readback::Image::read_framebuffer(display_size).save_png(&path)?;
+
+    println!("Rendered {frames} frames headlessly, saved the last one to {}", path.display());
     Ok(())
 }
 
-fn initialize_vertices() -> gl::BufferId {
-    let vertices = vec![
-        -0.5f32, -0.5f32,
-        1f32, 0f32, 0f32,
-        0.5f32, -0.5f32,
-        0f32, 1f32, 0f32,
-        0f32, 0.5f32,
-        0f32, 0f32, 1f32,
-    ];
-    let buffer = gl::gen_buffer();
-    gl::bind_buffer(gl::ARRAY_BUFFER, buffer);
-    gl::buffer_data(gl::ARRAY_BUFFER, vertices.as_slice(), gl::STREAM_DRAW);
-
-    buffer
+/// Number of frames to render for `--headless [frames]`, which defaults to a single frame
+#[cfg(not(target_os = "emscripten"))]
+fn headless_frames() -> Option<usize> {
+    args::has_flag("--headless")
+        .then(|| args::value("--headless").and_then(|frames| frames.parse().ok()).unwrap_or(1))
 }
 
+/// Persistent mapping failing is unexpected where buffer storage is available, and makes streaming slower
+fn report_stream_fallback(imgui: &Imgui) {
+    if imgui.stream_mode() == StreamMode::OrphaningFallback {
+        eprintln!("Failed to persistently map Dear ImGui's stream buffers, falling back to orphaning");
+    }
+}
+
+/// Prints the version and renderer of the created context for `--context-info`
+fn print_context_info() {
+    if !args::has_flag("--context-info") {
+        return;
+    }
+
+    let context_info = gl::ContextInfo::query();
+    println!(
+        "Created OpenGL{} {} context on {} ({}), GLSL {}, {} extensions",
+        if context_info.is_es { " ES" } else { "" },
+        context_info.version,
+        context_info.renderer,
+        context_info.vendor,
+        context_info.shading_language_version,
+        context_info.extensions.len());
+}
@@ -47,10 +47,6 @@ impl Program {
         }
     }
 
-    pub fn set_used(&self) {
-        gl::use_program(self.id);
-    }
-
     #[must_use]
     pub fn id(&self) -> gl::ProgramId {
         self.id
@@ -0,0 +1,135 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use thiserror::Error;
+
+mod gl {
+    #[cfg(not(target_os = "emscripten"))]
+    pub use gl::buffer::*;
+    pub use gl::framebuffer::*;
+    #[cfg(not(target_os = "emscripten"))]
+    pub use gl::sync::*;
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("PNG encoding error: {0}")]
+    PngEncoding(#[from] png::EncodingError),
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// RGBA8 pixels, starting with the top row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Reads `size` pixels from the origin of the bound read framebuffer, stalling until rendering finished
+    #[must_use]
+    pub fn read_framebuffer(size: (usize, usize)) -> Self {
+        let mut pixels = vec![0u8; gl::pixel_data_size(size, gl::RGBA, gl::UNSIGNED_BYTE)];
+        gl::read_pixels((0, 0), size, gl::RGBA, gl::UNSIGNED_BYTE, &mut pixels);
+        Self::from_bottom_up(size, pixels)
+    }
+
+    fn from_bottom_up(size: (usize, usize), mut pixels: Vec<u8>) -> Self {
+        gl::flip_vertically(&mut pixels, size.0 * 4);
+        Self { width: size.0, height: size.1, pixels }
+    }
+
+    /// # Errors
+    /// - Failed to create the file or its parent directories
+    /// - Failed to encode the PNG
+    pub fn save_png(&self, path: &Path) -> Result<()> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+}
+
+/// Copies the bound read framebuffer into a pixel pack buffer without stalling the pipeline.
+///
+/// The pixels are only mapped once [`is_ready`](Self::is_ready) reports the copy finished, usually a frame or two later.
+/// WebGL can't map buffers for reading, so in the browser the pixels are read synchronously on creation instead.
+#[derive(Debug)]
+pub struct PendingReadback {
+    #[cfg(not(target_os = "emscripten"))]
+    size: (usize, usize),
+    #[cfg(not(target_os = "emscripten"))]
+    buffer: gl::BufferId,
+    #[cfg(not(target_os = "emscripten"))]
+    fence: gl::Fence,
+    #[cfg(target_os = "emscripten")]
+    image: Image,
+}
+
+impl PendingReadback {
+    /// Starts reading `size` pixels from the origin of the bound read framebuffer
+    #[must_use]
+    pub fn new(size: (usize, usize)) -> Self {
+        #[cfg(not(target_os = "emscripten"))]
+        {
+            let buffer = gl::gen_buffer();
+            gl::bind_buffer(gl::PIXEL_PACK_BUFFER, buffer);
+            gl::buffer_data_uninitialized(
+                gl::PIXEL_PACK_BUFFER,
+                gl::pixel_data_size(size, gl::RGBA, gl::UNSIGNED_BYTE),
+                gl::STREAM_READ);
+            gl::read_pixels_to_buffer((0, 0), size, gl::RGBA, gl::UNSIGNED_BYTE, 0);
+            gl::bind_buffer(gl::PIXEL_PACK_BUFFER, gl::NO_BUFFER);
+
+            Self { size, buffer, fence: gl::Fence::new() }
+        }
+        #[cfg(target_os = "emscripten")]
+        Self { image: Image::read_framebuffer(size) }
+    }
+
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        #[cfg(not(target_os = "emscripten"))]
+        return self.fence.is_signaled();
+        #[cfg(target_os = "emscripten")]
+        return true;
+    }
+
+    /// Returns the read image, blocking until the copy finished if it isn't ready yet
+    ///
+    /// # Panics
+    /// - The driver failed to map the pixel pack buffer
+    #[must_use]
+    pub fn finish(self) -> Image {
+        #[cfg(not(target_os = "emscripten"))]
+        {
+            let len = gl::pixel_data_size(self.size, gl::RGBA, gl::UNSIGNED_BYTE);
+            // The buffer is only deleted once the mapping, which doesn't outlive this statement, is gone
+            let pixels = unsafe { gl::map_buffer_range::<u8>(gl::PIXEL_PACK_BUFFER, self.buffer, 0, len, gl::MAP_READ) }
+                .expect("Failed to map pixel pack buffer")
+                .to_vec();
+            gl::bind_buffer(gl::PIXEL_PACK_BUFFER, gl::NO_BUFFER);
+
+            Image::from_bottom_up(self.size, pixels)
+        }
+        #[cfg(target_os = "emscripten")]
+        self.image
+    }
+}
+
+#[cfg(not(target_os = "emscripten"))]
+impl Drop for PendingReadback {
+    fn drop(&mut self) {
+        gl::delete_buffer(&mut self.buffer);
+    }
+}
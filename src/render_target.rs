@@ -0,0 +1,76 @@
+use thiserror::Error;
+
+mod gl {
+    pub use gl::framebuffer::*;
+    pub use gl::state::*;
+    pub use gl::texture::*;
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Render target framebuffer is incomplete: {0:?}")]
+    IncompleteFramebuffer(gl::FramebufferStatus),
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Framebuffer rendering into a color texture, which can be sampled or shown in Dear ImGui afterwards.
+///
+/// The texture starts with the bottom row like any GL texture, so it's shown with flipped texture coordinates.
+#[derive(Debug)]
+pub struct RenderTarget {
+    size: (usize, usize),
+    framebuffer: gl::FramebufferId,
+    color_texture: gl::TextureId,
+}
+
+impl RenderTarget {
+    /// # Errors
+    /// - The framebuffer is incomplete, e.g. because the size exceeds the implementation limits
+    pub fn new(size: (usize, usize)) -> Result<Self> {
+        let color_texture = gl::gen_texture();
+        gl::bind_texture(gl::TEXTURE_2D, color_texture);
+        gl::texture_storage_2d(gl::TEXTURE_2D, 1, gl::TEXTURE_RGBA8, size);
+        gl::bind_texture(gl::TEXTURE_2D, gl::NO_TEXTURE);
+
+        // Dropped on errors, which deletes the framebuffer and texture again
+        let render_target = Self { size, framebuffer: gl::gen_framebuffer(), color_texture };
+        gl::bind_framebuffer(gl::FRAMEBUFFER, render_target.framebuffer);
+        gl::framebuffer_texture_2d(gl::FRAMEBUFFER, gl::color_attachment(0), color_texture);
+        let status = gl::check_framebuffer_status(gl::FRAMEBUFFER);
+        gl::bind_framebuffer(gl::FRAMEBUFFER, gl::DEFAULT_FRAMEBUFFER);
+
+        match status {
+            gl::FramebufferStatus::Complete => Ok(render_target),
+            status => Err(Error::IncompleteFramebuffer(status)),
+        }
+    }
+
+    #[must_use]
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Texture attached as color buffer, which stays valid as long as the render target
+    #[must_use]
+    pub fn color_texture(&self) -> gl::TextureId {
+        self.color_texture
+    }
+
+    /// Runs `draw` with the framebuffer bound and a viewport covering it.
+    ///
+    /// The default framebuffer is bound again afterwards, the viewport is left for the caller to reset.
+    pub fn render<F: FnOnce()>(&self, draw: F) {
+        gl::bind_framebuffer(gl::FRAMEBUFFER, self.framebuffer);
+        gl::viewport((0, 0), self.size);
+        draw();
+        gl::bind_framebuffer(gl::FRAMEBUFFER, gl::DEFAULT_FRAMEBUFFER);
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        gl::delete_framebuffer(&mut self.framebuffer);
+        gl::delete_texture(&mut self.color_texture);
+    }
+}
@@ -52,6 +52,13 @@ impl Resources {
         Ok(res)
     }
 
+    /// Resources relative to `root_path` rather than the executable, which tests don't run next to
+    #[cfg(test)]
+    #[must_use]
+    pub fn from_path(root_path: &Path) -> Self {
+        Self { root_path: root_path.to_path_buf() }
+    }
+
     /// # Errors
     /// - Fail to get exe path
     /// - Fail to get file metadata
@@ -84,6 +91,18 @@ impl Resources {
         Ok(String::from_utf8(buffer)?)
     }
 
+    /// # Errors
+    /// - Fail to get file metadata
+    /// - File too large
+    pub fn load_bytes(&self, resource_name: &str) -> Result<Vec<u8>> {
+        let mut file = fs::File::open(resource_name_to_path(&self.root_path, resource_name))?;
+        let file_len = usize::try_from(file.metadata()?.len()).map_err(|_| Error::TooLong)?;
+        let mut buffer: Vec<u8> = Vec::with_capacity(file_len);
+        file.read_to_end(&mut buffer)?;
+
+        Ok(buffer)
+    }
+
     /// # Errors
     /// - Fail to get exe path
     /// - Fail to get file metadata
@@ -0,0 +1,97 @@
+use anyhow::Result;
+
+use crate::frame_timer::FrameTimer;
+use crate::program::Program;
+use crate::resources::Resources;
+use crate::shader::{Kind, Shader};
+
+mod gl {
+    pub use gl::buffer::*;
+    pub use gl::pipeline::*;
+    pub use gl::program::*;
+    pub use gl::rendering::*;
+    pub use gl::vertex_array::*;
+    pub use gl::vertex_attrib::*;
+}
+
+/// The gamma corrected triangle shown behind the UI
+pub struct Scene {
+    program: Program,
+    pipeline_state: gl::PipelineState,
+    vao: gl::VertexArrayId,
+    vertex_buffer: gl::BufferId,
+}
+
+impl Scene {
+    /// # Errors
+    /// - Failed to load or compile the shaders
+    /// - Failed to link the program
+    pub fn new(resources: &Resources) -> Result<Self> {
+        let vertex_buffer = initialize_vertices();
+        let vertex_shader = {
+            let src = resources.load_string("vertex.glsl")?;
+            Shader::from_source(src.as_str(), Kind::Vertex)?
+        };
+        let fragment_shader = {
+            let src = resources.load_string("fragment.glsl")?;
+            Shader::from_source(src.as_str(), Kind::Fragment)?
+        };
+
+        let program = Program::from_shaders(&[
+            &vertex_shader, &fragment_shader])?;
+        let pipeline_state = gl::PipelineState::new(program.id());
+
+        let vao = gl::gen_vertex_array();
+        gl::bind_vertex_array(vao);
+        gl::bind_buffer(gl::ARRAY_BUFFER, vertex_buffer);
+        gl::enable_vertex_attrib_array(0);
+        gl::enable_vertex_attrib_array(1);
+
+        gl::vertex_attrib_pointer(0, gl::SIZE_2, gl::FLOAT, false, 5 * std::mem::size_of::<f32>(), 0);
+        gl::vertex_attrib_pointer(1, gl::SIZE_3, gl::FLOAT, false, 5 * std::mem::size_of::<f32>(), 2 * std::mem::size_of::<f32>());
+        gl::bind_vertex_array(gl::NO_VERTEX_ARRAY);
+
+        Ok(Self {
+            program,
+            pipeline_state,
+            vao,
+            vertex_buffer,
+        })
+    }
+
+    pub fn draw(&self, gamma: f32, frame_timer: &FrameTimer) {
+        gl::clear_color(0xFF000000);
+        gl::clear(gl::COLOR);
+
+        gl::bind_vertex_array(self.vao);
+        self.pipeline_state.apply();
+
+        let uniform_location = gl::uniform_location(self.program.id(), "gamma");
+        gl::uniform(uniform_location, gamma);
+        frame_timer.set_uniforms(self.program.id());
+        gl::draw_arrays(gl::TRIANGLES, 0, 3);
+    }
+}
+
+impl Drop for Scene {
+    fn drop(&mut self) {
+        gl::delete_vertex_array(&mut self.vao);
+        gl::delete_buffer(&mut self.vertex_buffer);
+    }
+}
+
+fn initialize_vertices() -> gl::BufferId {
+    let vertices = vec![
+        -0.5f32, -0.5f32,
+        1f32, 0f32, 0f32,
+        0.5f32, -0.5f32,
+        0f32, 1f32, 0f32,
+        0f32, 0.5f32,
+        0f32, 0f32, 1f32,
+    ];
+    let buffer = gl::gen_buffer();
+    gl::bind_buffer(gl::ARRAY_BUFFER, buffer);
+    gl::buffer_data(gl::ARRAY_BUFFER, vertices.as_slice(), gl::STREAM_DRAW);
+
+    buffer
+}
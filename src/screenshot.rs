@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::args;
+use crate::readback::{self, Image, PendingReadback};
+
+const DEFAULT_DIRECTORY: &str = "screenshots";
+
+/// Saves the framebuffer as timestamped PNG files, reading it back asynchronously so capturing doesn't stall a frame
+#[derive(Debug)]
+pub struct Screenshots {
+    directory: PathBuf,
+    pending: Vec<(SystemTime, PendingReadback)>,
+    latest: Option<Image>,
+}
+
+impl Screenshots {
+    #[must_use]
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory, pending: Vec::new(), latest: None }
+    }
+
+    /// Directory given through `--screenshot-dir <path>`, or `screenshots` within the working directory
+    #[must_use]
+    pub fn directory_from_args() -> PathBuf {
+        args::value("--screenshot-dir").map_or_else(|| PathBuf::from(DEFAULT_DIRECTORY), PathBuf::from)
+    }
+
+    #[must_use]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Starts capturing `size` pixels of the bound framebuffer, which must be called after rendering and before swapping
+    pub fn capture(&mut self, size: (usize, usize)) {
+        self.pending.push((SystemTime::now(), PendingReadback::new(size)));
+    }
+
+    /// Writes every screenshot whose readback finished, returning the paths of the written files
+    ///
+    /// # Errors
+    /// - Failed to create the directory or write the file
+    pub fn write_finished(&mut self) -> Result<Vec<PathBuf>, readback::Error> {
+        let mut written = Vec::new();
+        while let Some(index) = self.pending.iter().position(|(_, readback)| readback.is_ready()) {
+            let (time, readback) = self.pending.remove(index);
+            let path = self.directory.join(format!("screenshot_{}.png", format_timestamp(time)));
+            let image = readback.finish();
+            image.save_png(&path)?;
+            written.push(path);
+            self.latest = Some(image);
+        }
+
+        Ok(written)
+    }
+
+    /// Takes the most recently written screenshot, so it's only returned once
+    pub fn take_latest(&mut self) -> Option<Image> {
+        self.latest.take()
+    }
+}
+
+/// Formats as `YYYY-MM-DD_HH-MM-SS.mmm` in UTC, which sorts chronologically and is valid in file names
+fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let seconds_of_day = seconds % 86_400;
+
+    format!(
+        "{year:04}-{month:02}-{day:02}_{:02}-{:02}-{:02}.{:03}",
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+        since_epoch.subsec_millis())
+}
+
+/// Converts days since the unix epoch to a proleptic Gregorian date, following Howard Hinnant's algorithm
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
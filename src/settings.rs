@@ -0,0 +1,216 @@
+#[cfg(not(target_os = "emscripten"))]
+use std::fs;
+use std::io;
+#[cfg(not(target_os = "emscripten"))]
+use std::path::PathBuf;
+
+#[cfg(not(target_os = "emscripten"))]
+use crate::args;
+
+#[cfg(not(target_os = "emscripten"))]
+const DEFAULT_DIRECTORY: &str = "settings";
+/// Prefix of the `localStorage` keys, which are shared with every other page of the same origin
+#[cfg(target_os = "emscripten")]
+const STORAGE_KEY_PREFIX: &str = "opengl-web/";
+
+const SETTINGS_NAME: &str = "settings.ini";
+const IMGUI_INI_NAME: &str = "imgui.ini";
+
+/// Application settings kept between runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub gamma: f32,
+    /// In window coordinates, as reported by `window.size()`
+    pub window_size: (u32, u32),
+    pub vsync: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { gamma: 1f32, window_size: (500, 500), vsync: true }
+    }
+}
+
+impl Settings {
+    /// Loads the stored settings, keeping the defaults of missing or invalid values
+    #[must_use]
+    pub fn load(storage: &SettingsStorage) -> Self {
+        let mut settings = Self::default();
+        let Some(text) = storage.load(SETTINGS_NAME) else {
+            return settings;
+        };
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let parsed = line.split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .and_then(|(key, value)| settings.parse_value(key, value));
+            if parsed.is_none() {
+                eprintln!("Ignoring invalid setting {line}");
+            }
+        }
+
+        settings
+    }
+
+    fn parse_value(&mut self, key: &str, value: &str) -> Option<()> {
+        match key {
+            "gamma" => self.gamma = value.parse().ok().filter(|gamma: &f32| gamma.is_finite())?,
+            "window_size" => {
+                let (width, height) = value.split_once('x')?;
+                let window_size = (width.parse().ok()?, height.parse().ok()?);
+                // SDL can't create windows without area
+                self.window_size = Some(window_size).filter(|&(width, height)| width > 0 && height > 0)?;
+            }
+            "vsync" => self.vsync = value.parse().ok()?,
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    /// # Errors
+    /// - Failed to write the settings file
+    pub fn save(&self, storage: &SettingsStorage) -> io::Result<()> {
+        let text = format!(
+            "gamma = {}\nwindow_size = {}x{}\nvsync = {}\n",
+            self.gamma,
+            self.window_size.0,
+            self.window_size.1,
+            self.vsync);
+        storage.save(SETTINGS_NAME, &text)
+    }
+}
+
+/// Keeps settings and Dear ImGui's window layout as files on desktop and in `localStorage` in the browser
+#[derive(Debug, Clone)]
+pub struct SettingsStorage {
+    #[cfg(not(target_os = "emscripten"))]
+    directory: PathBuf,
+}
+
+impl SettingsStorage {
+    /// Storage within the directory given through `--settings-dir <path>`, or `settings` within the working directory
+    #[must_use]
+    pub fn from_args() -> Self {
+        #[cfg(not(target_os = "emscripten"))]
+        return Self {
+            directory: args::value("--settings-dir").map_or_else(|| PathBuf::from(DEFAULT_DIRECTORY), PathBuf::from),
+        };
+        #[cfg(target_os = "emscripten")]
+        return Self {};
+    }
+
+    /// Dear ImGui's window positions, sizes and collapsed states
+    #[must_use]
+    pub fn load_imgui_ini(&self) -> Option<String> {
+        self.load(IMGUI_INI_NAME)
+    }
+
+    /// # Errors
+    /// - Failed to write the file
+    pub fn save_imgui_ini(&self, ini: &str) -> io::Result<()> {
+        self.save(IMGUI_INI_NAME, ini)
+    }
+
+    fn load(&self, name: &str) -> Option<String> {
+        #[cfg(not(target_os = "emscripten"))]
+        return fs::read_to_string(self.directory.join(name)).ok();
+        #[cfg(target_os = "emscripten")]
+        {
+            use crate::emscripten::{emscripten, to_js_string_literal};
+            // `localStorage` throws where storage is disabled, e.g. for sandboxed frames
+            let key = to_js_string_literal(&format!("{STORAGE_KEY_PREFIX}{name}"));
+            return emscripten::run_script_string(&format!(
+                "(function () {{ try {{ return localStorage.getItem({key}); }} catch (error) {{ return null; }} }})()"));
+        }
+    }
+
+    fn save(&self, name: &str, contents: &str) -> io::Result<()> {
+        #[cfg(not(target_os = "emscripten"))]
+        return fs::create_dir_all(&self.directory)
+            .and_then(|()| fs::write(self.directory.join(name), contents));
+        #[cfg(target_os = "emscripten")]
+        {
+            use crate::emscripten::{emscripten, to_js_string_literal};
+            let key = to_js_string_literal(&format!("{STORAGE_KEY_PREFIX}{name}"));
+            let value = to_js_string_literal(contents);
+            // Failing to persist isn't reported back, the page keeps working with the current settings
+            emscripten::run_script(&format!("try {{ localStorage.setItem({key}, {value}); }} catch (error) {{}}"));
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Settings;
+    #[cfg(not(target_os = "emscripten"))]
+    use super::{SettingsStorage, SETTINGS_NAME};
+
+    /// Storage in a fresh directory of its own, as tests run in parallel
+    #[cfg(not(target_os = "emscripten"))]
+    fn temporary_storage(name: &str) -> SettingsStorage {
+        let directory = std::env::temp_dir().join(format!("opengl-web-settings-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        SettingsStorage { directory }
+    }
+
+    fn parse(key: &str, value: &str) -> Option<Settings> {
+        let mut settings = Settings::default();
+        settings.parse_value(key, value).map(|()| settings)
+    }
+
+    #[test]
+    fn parses_values() {
+        assert_eq!(parse("gamma", "2.2").map(|settings| settings.gamma), Some(2.2f32));
+        assert_eq!(parse("window_size", "1280x720").map(|settings| settings.window_size), Some((1280, 720)));
+        assert_eq!(parse("vsync", "false").map(|settings| settings.vsync), Some(false));
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert_eq!(parse("gamma", "bright"), None);
+        assert_eq!(parse("window_size", "1280"), None);
+        assert_eq!(parse("window_size", "1280x-720"), None);
+        assert_eq!(parse("window_size", "0x0"), None);
+        assert_eq!(parse("window_size", "1280x0"), None);
+        assert_eq!(parse("vsync", "yes"), None);
+        assert_eq!(parse("fullscreen", "true"), None);
+    }
+
+    #[test]
+    fn rejects_non_finite_gamma() {
+        assert_eq!(parse("gamma", "NaN"), None);
+        assert_eq!(parse("gamma", "inf"), None);
+        assert_eq!(parse("gamma", "-inf"), None);
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[test]
+    fn round_trips_through_storage() {
+        let storage = temporary_storage("round-trip");
+        let settings = Settings { gamma: 2.2f32, window_size: (1280, 720), vsync: false };
+        settings.save(&storage).expect("Failed to save settings");
+
+        assert_eq!(Settings::load(&storage), settings);
+        let _ = std::fs::remove_dir_all(&storage.directory);
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[test]
+    fn keeps_defaults_for_missing_storage() {
+        assert_eq!(Settings::load(&temporary_storage("missing")), Settings::default());
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    #[test]
+    fn skips_invalid_lines() {
+        let storage = temporary_storage("invalid-lines");
+        let text = "gamma = inf\nno separator\n\nwindow_size = 800 x 600\nunknown = 1\n  vsync=false  \n";
+        storage.save(SETTINGS_NAME, text).expect("Failed to save settings");
+
+        let settings = Settings::load(&storage);
+        assert_eq!(settings, Settings { vsync: false, ..Settings::default() });
+        let _ = std::fs::remove_dir_all(&storage.directory);
+    }
+}
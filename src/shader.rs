@@ -31,7 +31,7 @@ pub enum Error {
 
 type Result<T> = std::result::Result<T, Error>;
 
-#[cfg(target_os = "emscripten")]
+#[cfg(gl_es)]
 mod shader_kind {
     #[derive(Debug, Copy, Clone, Eq, PartialEq)]
     pub enum Kind {
@@ -49,7 +49,7 @@ mod shader_kind {
     }
 }
 
-#[cfg(not(target_os = "emscripten"))]
+#[cfg(not(gl_es))]
 mod shader_kind {
     #[derive(Debug, Copy, Clone, Eq, PartialEq)]
     pub enum Kind {
@@ -0,0 +1,244 @@
+#[cfg(not(gl_es))]
+use std::collections::VecDeque;
+#[cfg(not(gl_es))]
+use std::mem::MaybeUninit;
+#[cfg(not(gl_es))]
+use std::time::Duration;
+
+mod gl {
+    pub use gl::buffer::*;
+    #[cfg(not(gl_es))]
+    pub use gl::sync::*;
+}
+
+/// Every allocation is aligned to at least this many bytes, the buffer size is rounded up to a multiple of it
+const MAX_ALIGNMENT: usize = 256;
+
+/// How a [`StreamBuffer`] hands its data to the GPU
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StreamMode {
+    /// Persistently mapped buffer storage, whose regions are reused once the fence of their frame got signaled
+    #[cfg_attr(gl_es, allow(dead_code))]
+    PersistentMapping,
+    /// Storage respecified on every wrap around, as buffer storage isn't available, e.g. on GLES
+    Orphaning,
+    /// Storage respecified on every wrap around, as persistently mapping the buffer storage failed
+    OrphaningFallback,
+}
+
+/// Region of the buffer the GPU may still be reading from, starting at a stream position
+#[cfg(not(gl_es))]
+#[derive(Debug)]
+struct FencedRegion {
+    start: usize,
+    fence: gl::Fence,
+}
+
+/// Ring buffer handing out per-frame suballocations for dynamic geometry.
+///
+/// Allocations are addressed through monotonically increasing stream positions, whose remainder by the
+/// buffer size is the byte offset into the buffer. Where buffer storage is available the buffer is persistently
+/// mapped, every finished frame is fenced and writes wait for the fence of any frame whose data they would
+/// overwrite. Otherwise, e.g. on GLES, the storage is orphaned whenever the stream wraps around instead.
+#[derive(Debug)]
+pub struct StreamBuffer {
+    id: gl::BufferId,
+    target: gl::BufferTarget,
+    size: usize,
+    head: usize,
+    frame_start: usize,
+    mode: StreamMode,
+    #[cfg(not(gl_es))]
+    fenced_regions: VecDeque<FencedRegion>,
+    /// `None` if the buffer is streamed through orphaning
+    #[cfg(not(gl_es))]
+    mapping: Option<gl::MappedBuffer<MaybeUninit<u8>>>,
+}
+
+impl StreamBuffer {
+    #[must_use]
+    pub fn new(target: gl::BufferTarget, size: usize) -> Self {
+        let size = align_up(size.max(1), MAX_ALIGNMENT);
+        let id = gl::gen_buffer();
+        gl::bind_buffer(target, id);
+
+        let mut stream_buffer = Self {
+            id,
+            target,
+            size,
+            head: 0,
+            frame_start: 0,
+            mode: StreamMode::Orphaning,
+            #[cfg(not(gl_es))]
+            fenced_regions: VecDeque::new(),
+            #[cfg(not(gl_es))]
+            mapping: None,
+        };
+        stream_buffer.allocate();
+        stream_buffer
+    }
+
+    /// Copies `data` into the buffer and returns its byte offset within the buffer.
+    /// `alignment` must be a power of two no larger than 256.
+    /// Returns `None` if the data written during the current frame would exceed the buffer size.
+    ///
+    /// When orphaning, data pushed before the stream wrapped around within the same frame stays in the orphaned
+    /// storage, which draws issued before the wrap keep reading from. Draws issued afterwards only see the new storage.
+    ///
+    /// The buffer is left bound to its target.
+    pub fn push<T: Copy>(&mut self, data: &[T], alignment: usize) -> Option<usize> {
+        debug_assert!(alignment.is_power_of_two() && alignment <= MAX_ALIGNMENT);
+        let len = std::mem::size_of_val(data);
+        let alignment = alignment.max(std::mem::align_of::<T>());
+
+        let mut start = align_up(self.head, alignment);
+        if start % self.size + len > self.size {
+            start = align_up(start + 1, self.size);
+        }
+        let end = start + len;
+        if end - self.frame_start > self.size {
+            return None;
+        }
+
+        gl::bind_buffer(self.target, self.id);
+        #[cfg(not(gl_es))]
+        if self.mapping.is_some() {
+            self.write_mapped(start, data);
+        } else {
+            self.write_orphaning(start, data);
+        }
+        #[cfg(gl_es)]
+        self.write_orphaning(start, data);
+        self.head = end;
+
+        Some(start % self.size)
+    }
+
+    /// Grows the buffer so at least `size` bytes, including alignment padding, fit into a single frame.
+    /// Must only be called before anything has been pushed during the current frame.
+    ///
+    /// The new buffer is left bound to the target.
+    pub fn reserve(&mut self, size: usize) {
+        debug_assert_eq!(self.head, self.frame_start);
+        // Wrapping around may skip up to the size of the largest allocation at the end of the buffer
+        let required_size = 2 * size;
+        if required_size <= self.size {
+            return;
+        }
+
+        // Replacing `self` would drop the old buffer only after binding the new one, unbinding it again
+        self.release();
+        self.id = gl::gen_buffer();
+        self.size = align_up(required_size.next_power_of_two(), MAX_ALIGNMENT);
+        self.head = 0;
+        self.frame_start = 0;
+        gl::bind_buffer(self.target, self.id);
+        self.allocate();
+    }
+
+    /// Fences everything written since the previous call.
+    /// Must be called once all draw calls sourcing data from the current frame have been issued.
+    pub fn finish_frame(&mut self) {
+        if self.head == self.frame_start {
+            return;
+        }
+
+        #[cfg(not(gl_es))]
+        if self.mapping.is_some() {
+            self.fenced_regions.push_back(FencedRegion { start: self.frame_start, fence: gl::Fence::new() });
+        }
+        self.frame_start = self.head;
+    }
+
+    #[must_use]
+    pub fn mode(&self) -> StreamMode {
+        self.mode
+    }
+
+    pub fn bind(&self) {
+        gl::bind_buffer(self.target, self.id);
+    }
+
+    /// Allocates the storage of the freshly generated buffer bound to the target, persistently mapping it if possible
+    fn allocate(&mut self) {
+        #[cfg(not(gl_es))]
+        if gl::buffer_storage_supported() {
+            gl::buffer_storage_uninitialized(
+                self.target,
+                self.size,
+                gl::STORAGE_MAP_WRITE | gl::STORAGE_MAP_PERSISTENT | gl::STORAGE_MAP_COHERENT);
+            // The mapping is dropped before the buffer is deleted and the storage is immutable
+            self.mapping = unsafe {
+                gl::map_buffer_range_write(
+                    self.target,
+                    self.id,
+                    0,
+                    self.size,
+                    gl::MAP_WRITE | gl::MAP_PERSISTENT | gl::MAP_COHERENT)
+            };
+            if self.mapping.is_some() {
+                self.mode = StreamMode::PersistentMapping;
+                return;
+            }
+
+            // Immutable storage can't be respecified for orphaning, so it takes another buffer
+            self.mode = StreamMode::OrphaningFallback;
+            gl::delete_buffer(&mut self.id);
+            self.id = gl::gen_buffer();
+            gl::bind_buffer(self.target, self.id);
+        }
+
+        gl::buffer_data_uninitialized(self.target, self.size, gl::STREAM_DRAW);
+    }
+
+    /// Unmaps and deletes the buffer, without touching the binding of the target if it's bound elsewhere
+    fn release(&mut self) {
+        #[cfg(not(gl_es))]
+        {
+            drop(self.mapping.take());
+            self.fenced_regions.clear();
+        }
+        gl::delete_buffer(&mut self.id);
+    }
+
+    /// Persistently mapped path: Waits for the GPU to release the overwritten range, then copies directly
+    #[cfg(not(gl_es))]
+    fn write_mapped<T: Copy>(&mut self, start: usize, data: &[T]) {
+        let len = std::mem::size_of_val(data);
+        let end = start + len;
+        while let Some(region) = self.fenced_regions.front() {
+            if region.start + self.size >= end {
+                break;
+            }
+
+            let region = self.fenced_regions.pop_front().unwrap();
+            // A failed wait means the context is gone, at which point there is nothing left to protect
+            while region.fence.client_wait(Duration::from_secs(1)) == gl::WaitStatus::Timeout {}
+        }
+
+        let offset = start % self.size;
+        let mapping = self.mapping.as_mut().unwrap();
+        let destination = &mut mapping[offset..offset + len];
+        // Copied as possibly uninitialized bytes, as `T` may contain padding
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr().cast(), destination.as_mut_ptr(), len) };
+    }
+
+    /// Orphaning path: Wrapping around respecifies the storage, so pending draws keep reading the old one
+    fn write_orphaning<T: Copy>(&mut self, start: usize, data: &[T]) {
+        if start / self.size > self.head.saturating_sub(1) / self.size {
+            gl::buffer_data_uninitialized(self.target, self.size, gl::STREAM_DRAW);
+        }
+
+        gl::buffer_sub_data(self.target, start % self.size, data);
+    }
+}
+
+impl Drop for StreamBuffer {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+const fn align_up(value: usize, alignment: usize) -> usize {
+    value.div_ceil(alignment) * alignment
+}
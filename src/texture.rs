@@ -21,6 +21,7 @@ pub enum ImageLoadingError {
 
 type Result<T> = std::result::Result<T, ImageLoadingError>;
 
+#[derive(Debug)]
 pub struct Texture {
     handle: GLuint,
     width: usize,
@@ -77,20 +78,20 @@ impl Texture {
         let gl_linear = unsafe { GLint::try_from(gl::sys::LINEAR).unwrap_unchecked() };
 
         unsafe {
-            #[cfg(target_os = "emscripten")]
+            #[cfg(gl_es)]
             gl::sys::GenTextures(1, &mut handle);
-            #[cfg(not(target_os = "emscripten"))]
+            #[cfg(not(gl_es))]
             gl::sys::CreateTextures(gl::sys::TEXTURE_2D, 1 as GLsizei, &mut handle);
-            gl::sys::BindTexture(gl::sys::TEXTURE_2D, handle);
+            bind_texture_2d(handle);
 
-            #[cfg(target_os = "emscripten")]
+            #[cfg(gl_es)]
             {
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_MIN_FILTER, gl_linear);
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_MAG_FILTER, gl_linear);
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_WRAP_S, gl::sys::CLAMP_TO_EDGE as GLint);
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_WRAP_T, gl::sys::CLAMP_TO_EDGE as GLint);
             }
-            #[cfg(not(target_os = "emscripten"))]
+            #[cfg(not(gl_es))]
             {
                 gl::sys::TextureParameteri(handle, gl::sys::TEXTURE_MIN_FILTER, gl_linear);
                 gl::sys::TextureParameteri(handle, gl::sys::TEXTURE_MAG_FILTER, gl_linear);
@@ -111,9 +112,9 @@ impl Texture {
                 gl::sys::UNSIGNED_BYTE,
                 image_data.as_ptr().cast::<c_void>(),
             );
-            #[cfg(target_os = "emscripten")]
+            #[cfg(gl_es)]
             gl::sys::GenerateMipmap(gl::sys::TEXTURE_2D);
-            #[cfg(not(target_os = "emscripten"))]
+            #[cfg(not(gl_es))]
             gl::sys::GenerateTextureMipmap(handle);
         }
         Ok(Self {
@@ -135,20 +136,20 @@ impl Texture {
         let gl_rgba = unsafe { GLint::try_from(gl::sys::RGBA32F).unwrap_unchecked() };
 
         unsafe {
-//            #[cfg(target_os = "emscripten")]
+//            #[cfg(gl_es)]
             gl::sys::GenTextures(1, &mut handle);
-//            #[cfg(not(target_os = "emscripten"))]
+//            #[cfg(not(gl_es))]
 //            gl::CreateTextures(gl::TEXTURE_2D, 1 as GLsizei, &mut handle);
 
-            gl::sys::BindTexture(gl::sys::TEXTURE_2D, handle);
+            bind_texture_2d(handle);
 
-//            #[cfg(target_os = "emscripten")]
+//            #[cfg(gl_es)]
             {
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_MIN_FILTER, gl_linear);
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_MAG_FILTER, gl_linear);
             }
 /*
-            #[cfg(not(target_os = "emscripten"))]
+            #[cfg(not(gl_es))]
             {
                 gl::TextureParameteri(handle, gl::TEXTURE_MIN_FILTER, gl_linear);
                 gl::TextureParameteri(handle, gl::TEXTURE_MAG_FILTER, gl_linear);
@@ -168,9 +169,9 @@ impl Texture {
                 gl::sys::UNSIGNED_BYTE,
                 image_data.as_ptr().cast::<c_void>(),
             );
-//            #[cfg(target_os = "emscripten")]
+//            #[cfg(gl_es)]
 //            gl::GenerateMipmap(gl::TEXTURE_2D);
-//            #[cfg(not(target_os = "emscripten"))]
+//            #[cfg(not(gl_es))]
 //            gl::GenerateTextureMipmap(handle);
         }
         Ok(Self {
@@ -190,18 +191,18 @@ impl Texture {
         let gl_rgba = unsafe { GLint::try_from(gl::sys::RGBA32F).unwrap_unchecked() };
 
         unsafe {
-            #[cfg(target_os = "emscripten")]
+            #[cfg(gl_es)]
             gl::sys::GenTextures(1, &mut handle);
-            #[cfg(not(target_os = "emscripten"))]
+            #[cfg(not(gl_es))]
             gl::sys::CreateTextures(gl::sys::TEXTURE_2D, 1 as GLsizei, &mut handle);
-            gl::sys::BindTexture(gl::sys::TEXTURE_2D, handle);
+            bind_texture_2d(handle);
 
-            #[cfg(target_os = "emscripten")]
+            #[cfg(gl_es)]
             {
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_MIN_FILTER, gl::sys::LINEAR_MIPMAP_LINEAR as GLint);
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_MAG_FILTER, gl_linear);
             }
-            #[cfg(not(target_os = "emscripten"))]
+            #[cfg(not(gl_es))]
             {
                 gl::sys::TextureParameteri(handle, gl::sys::TEXTURE_MIN_FILTER, gl::sys::LINEAR_MIPMAP_LINEAR as GLint);
                 gl::sys::TextureParameteri(handle, gl::sys::TEXTURE_MAG_FILTER, gl_linear);
@@ -230,9 +231,9 @@ impl Texture {
                 image_data.gl_type,
                 image_data.ptr,
             );
-            #[cfg(target_os = "emscripten")]
+            #[cfg(gl_es)]
             gl::sys::GenerateMipmap(gl::sys::TEXTURE_2D);
-            #[cfg(not(target_os = "emscripten"))]
+            #[cfg(not(gl_es))]
             gl::sys::GenerateTextureMipmap(handle);
         }
         // We don't require to check width & height as they've been validated above
@@ -256,11 +257,11 @@ impl Texture {
         let gl_rgba = unsafe { GLint::try_from(gl::sys::RGBA32F).unwrap_unchecked() };
 
         unsafe {
-            #[cfg(target_os = "emscripten")]
+            #[cfg(gl_es)]
             gl::sys::GenTextures(1, &mut handle);
-            #[cfg(not(target_os = "emscripten"))]
+            #[cfg(not(gl_es))]
             gl::sys::CreateTextures(gl::sys::TEXTURE_2D, 1 as GLsizei, &mut handle);
-            gl::sys::BindTexture(gl::sys::TEXTURE_2D, handle);
+            bind_texture_2d(handle);
 
             gl::sys::TexImage2D(
                 gl::sys::TEXTURE_2D,
@@ -273,12 +274,12 @@ impl Texture {
                 gl::sys::UNSIGNED_BYTE,
                 std::ptr::null(),
             );
-            #[cfg(target_os = "emscripten")]
+            #[cfg(gl_es)]
             {
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_MIN_FILTER, gl_linear);
                 gl::sys::TexParameteri(gl::sys::TEXTURE_2D, gl::sys::TEXTURE_MAG_FILTER, gl_linear);
             }
-            #[cfg(not(target_os = "emscripten"))]
+            #[cfg(not(gl_es))]
             {
                 gl::sys::TextureParameteri(handle, gl::sys::TEXTURE_MIN_FILTER, gl_linear);
                 gl::sys::TextureParameteri(handle, gl::sys::TEXTURE_MAG_FILTER, gl_linear);
@@ -308,6 +309,19 @@ impl Texture {
     }
 }
 
+impl Drop for Texture {
+    fn drop(&mut self) {
+        let mut texture_id = unsafe { gl::texture::TextureId::from_raw_handle(self.handle) };
+        gl::texture::delete_texture(&mut texture_id);
+    }
+}
+
+/// Binding through the wrapper keeps its state cache coherent
+fn bind_texture_2d(handle: GLuint) {
+    let texture_id = unsafe { gl::texture::TextureId::from_raw_handle(handle) };
+    gl::texture::bind_texture(gl::texture::TEXTURE_2D, texture_id);
+}
+
 const fn format_from_depth(depth: usize) -> Result<GLenum> {
     match depth {
         1 => Ok(gl::sys::RED),